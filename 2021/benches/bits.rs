@@ -0,0 +1,154 @@
+use std::hint::black_box;
+
+use aoc2021::day16::{self, BitReader, LengthType, Packet, PacketNode, ParserState};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const EXAMPLE: &str = "9C0141080250320F1802104A08";
+
+/// A pseudo-random tree of at most |depth| levels below the root.
+fn generated_packet(state: &mut u32, depth: u32) -> Packet {
+    let mut next = || {
+        *state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        *state >> 8
+    };
+    let version = (next() % 8) as u64;
+    let kind = next() % 8;
+    if depth == 0 || kind == 4 {
+        let value = (next() as u64) << (next() % 40);
+        return Packet {
+            node: PacketNode::Literal(value),
+            version,
+        };
+    }
+
+    let children = 1 + next() % 5;
+    let mut subpackets: Vec<_> = (0..children)
+        .map(|_| generated_packet(state, depth - 1))
+        .collect();
+    let node = match kind {
+        0 => PacketNode::Sum(subpackets),
+        1 => PacketNode::Product(subpackets),
+        2 => PacketNode::Minimum(subpackets),
+        3 => PacketNode::Maximum(subpackets),
+        _ => {
+            subpackets.truncate(1);
+            let left = Box::new(subpackets.pop().unwrap());
+            let right = Box::new(generated_packet(state, depth - 1));
+            match kind {
+                5 => PacketNode::GreaterThan(left, right),
+                6 => PacketNode::LessThan(left, right),
+                _ => PacketNode::Equals(left, right),
+            }
+        }
+    };
+    Packet { node, version }
+}
+
+/// A sum of a thousand generated trees, a few hundred thousand hex digits long. Operators over
+/// only literals give their length in bits, so both length types are exercised.
+fn generated_transmission() -> String {
+    let mut state = 0x2021_0016;
+    let root = Packet {
+        node: PacketNode::Sum((0..1000).map(|_| generated_packet(&mut state, 4)).collect()),
+        version: 0,
+    };
+    day16::encode(&root, |packet| {
+        if packet
+            .subpackets()
+            .all(|subpacket| subpacket.literal().is_some())
+        {
+            LengthType::Bits
+        } else {
+            LengthType::Count
+        }
+    })
+    .unwrap()
+}
+
+/// The reader |day16::ParserState| replaced, which decodes one hex digit at a time. Kept as a
+/// baseline.
+struct Nibbles<'a> {
+    current: u8,
+    current_bits: u8,
+    rest: &'a str,
+    bit_position: usize,
+}
+
+impl<'a> Nibbles<'a> {
+    fn new(input: &'a str) -> Self {
+        Nibbles {
+            current: 0,
+            current_bits: 0,
+            rest: input,
+            bit_position: 0,
+        }
+    }
+
+    /// Loads the next hex digit into |current|, or fails like |BitReader::read_bits|.
+    fn take_char(&mut self) -> Result<(), Option<char>> {
+        let c = self.rest.chars().next().ok_or(None)?;
+        self.current = c.to_digit(16).ok_or(Some(c))? as u8;
+        self.current_bits = 4;
+        self.rest = &self.rest[c.len_utf8()..];
+        Ok(())
+    }
+}
+
+impl BitReader for Nibbles<'_> {
+    fn read_bits(&mut self, requested_bits: u8) -> Result<u64, Option<char>> {
+        let mut bits = 0u64;
+        let mut remaining_bits = requested_bits;
+        while remaining_bits > 0 {
+            if self.current_bits == 0 {
+                self.take_char()?;
+            }
+            let taken = remaining_bits.min(self.current_bits);
+            self.current_bits -= taken;
+            bits = (bits << taken) | (self.current >> self.current_bits) as u64;
+            self.current &= (1 << self.current_bits) - 1;
+            self.bit_position += taken as usize;
+            remaining_bits -= taken;
+        }
+        Ok(bits)
+    }
+
+    fn bit_position(&self) -> usize {
+        self.bit_position
+    }
+
+    fn digit_position(&self) -> usize {
+        self.bit_position + self.current_bits as usize
+    }
+}
+
+fn bench_bits(c: &mut Criterion) {
+    // the readers agree on where a malformed transmission goes wrong, too
+    for malformed in ["", "D2FE2", "EE0", "D2xE28", "38006F4529120"] {
+        assert_eq!(
+            day16::parse_packet(&mut Nibbles::new(malformed)),
+            day16::parse_packet(&mut ParserState::new(malformed))
+        );
+    }
+
+    for (name, transmission) in [
+        ("example", EXAMPLE.to_owned()),
+        ("generated", generated_transmission()),
+    ] {
+        assert_eq!(
+            day16::parse_packet(&mut Nibbles::new(&transmission)),
+            day16::parse_packet(&mut ParserState::new(&transmission))
+        );
+        let mut group = c.benchmark_group(format!("day16/parse/{}", name));
+        group.throughput(Throughput::Bytes(transmission.len() as u64));
+        group.bench_function("buffered", |b| {
+            b.iter(|| day16::parse_packet(&mut ParserState::new(black_box(&transmission))))
+        });
+        group.bench_function("nibbles", |b| {
+            b.iter(|| day16::parse_packet(&mut Nibbles::new(black_box(&transmission))))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_bits);
+criterion_main!(benches);
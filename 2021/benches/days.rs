@@ -0,0 +1,11 @@
+use aoc_common::bench::bench_days;
+use aoc_common::input::DataDir;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn bench(c: &mut Criterion) {
+    let data_dir = DataDir::from_env_or_crate(env!("CARGO_MANIFEST_DIR"));
+    bench_days(c, aoc2021::DAYS, &data_dir);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
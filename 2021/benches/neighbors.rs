@@ -0,0 +1,176 @@
+use std::hint::black_box;
+
+use aoc2021::{day11, day15};
+use aoc_common::coord::{Moore, Neighborhood, VonNeumann};
+use aoc_common::grid::Grid;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const OCTOPUSES: &str = "\
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526";
+
+const RISKS: &str = "\
+1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
+/// The |Coords| that the lazy iterator replaced, which collects the bounding box around the
+/// center into a Vec on every call. Kept as a baseline, along with neighborhoods that use it.
+mod vec_coords {
+    use aoc_common::coord::{Coord, Neighborhood};
+
+    pub struct Coords {
+        coord_range: Vec<Coord>,
+        coord_index: usize,
+    }
+
+    impl Coords {
+        pub fn new_surrounding(max: Coord, center: Coord) -> Self {
+            let (top_left, bottom_right) = get_bounding_coords(&max, &center);
+
+            Coords {
+                coord_range: to(top_left, bottom_right)
+                    .filter(|c| *c != center)
+                    .collect(),
+                coord_index: 0,
+            }
+        }
+
+        pub fn new_neighbors(max: Coord, center: Coord) -> Self {
+            let (top_left, bottom_right) = get_bounding_coords(&max, &center);
+
+            Coords {
+                coord_range: to(top_left, bottom_right)
+                    .filter(|c| (c.row != center.row) ^ (c.col != center.col))
+                    .collect(),
+                coord_index: 0,
+            }
+        }
+    }
+
+    fn to(start: Coord, end: Coord) -> impl Iterator<Item = Coord> {
+        (start.row..=end.row)
+            .flat_map(move |row| (start.col..=end.col).map(move |col| Coord { row, col }))
+    }
+
+    fn get_bounding_coords(max: &Coord, center: &Coord) -> (Coord, Coord) {
+        let top_left = Coord {
+            row: (center.row - 1).max(0),
+            col: (center.col - 1).max(0),
+        };
+        let bottom_right = Coord {
+            row: (center.row + 1).min(max.row),
+            col: (center.col + 1).min(max.col),
+        };
+        (top_left, bottom_right)
+    }
+
+    impl Iterator for Coords {
+        type Item = Coord;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            match self.coord_range.get(self.coord_index) {
+                Some(coord) => {
+                    self.coord_index += 1;
+                    Some(*coord)
+                }
+                _ => None,
+            }
+        }
+    }
+
+    pub struct Moore;
+
+    impl Neighborhood for Moore {
+        fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord> {
+            Coords::new_surrounding(max, center)
+        }
+
+        fn min_steps(&self, from: Coord, to: Coord) -> u32 {
+            from.chebyshev_distance(&to)
+        }
+    }
+
+    pub struct VonNeumann;
+
+    impl Neighborhood for VonNeumann {
+        fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord> {
+            Coords::new_neighbors(max, center)
+        }
+
+        fn min_steps(&self, from: Coord, to: Coord) -> u32 {
+            from.manhattan_distance(&to)
+        }
+    }
+}
+
+/// Sums the number of neighbors of every cell in |grid|.
+fn count_all(grid: &Grid<u8>, neighborhood: &impl Neighborhood) -> usize {
+    grid.coords()
+        .map(|coord| grid.neighbors(neighborhood, black_box(coord)).count())
+        .sum()
+}
+
+/// Benches the lazy neighbor iteration against the Vec-collecting baseline, on its own and in
+/// the days that lean on it, using the puzzle examples so that no inputs are needed.
+fn bench_neighbors(c: &mut Criterion) {
+    let grid = Grid::new(100, 100, 0u8);
+    let mut group = c.benchmark_group("coords/surrounding");
+    group.bench_function("lazy", |b| b.iter(|| count_all(&grid, &Moore)));
+    group.bench_function("vec", |b| b.iter(|| count_all(&grid, &vec_coords::Moore)));
+    group.finish();
+
+    let mut group = c.benchmark_group("coords/neighbors");
+    group.bench_function("lazy", |b| b.iter(|| count_all(&grid, &VonNeumann)));
+    group.bench_function("vec", |b| {
+        b.iter(|| count_all(&grid, &vec_coords::VonNeumann))
+    });
+    group.finish();
+
+    let octopuses = day11::parse_input(OCTOPUSES).unwrap();
+    assert_eq!(
+        day11::count_flashes(&octopuses, 100, &Moore),
+        day11::count_flashes(&octopuses, 100, &vec_coords::Moore)
+    );
+    let mut group = c.benchmark_group("day11/example");
+    group.bench_function("lazy", |b| {
+        b.iter(|| day11::count_flashes(black_box(&octopuses), 100, &Moore))
+    });
+    group.bench_function("vec", |b| {
+        b.iter(|| day11::count_flashes(black_box(&octopuses), 100, &vec_coords::Moore))
+    });
+    group.finish();
+
+    let risks = day15::parse_input(RISKS).unwrap();
+    let tiled = day15::RiskMap::new(risks, 5);
+    assert_eq!(
+        day15::a_star_in(&tiled, &VonNeumann),
+        day15::a_star_in(&tiled, &vec_coords::VonNeumann)
+    );
+    let mut group = c.benchmark_group("day15/example");
+    group.bench_function("lazy", |b| {
+        b.iter(|| day15::a_star_in(black_box(&tiled), &VonNeumann))
+    });
+    group.bench_function("vec", |b| {
+        b.iter(|| day15::a_star_in(black_box(&tiled), &vec_coords::VonNeumann))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_neighbors);
+criterion_main!(benches);
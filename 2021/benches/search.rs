@@ -0,0 +1,84 @@
+use std::hint::black_box;
+
+use aoc2021::day15::{self, RiskMap};
+use aoc_common::coord::{Coord, VonNeumann};
+use aoc_common::grid::Grid;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const RISKS: &str = "\
+1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
+/// A |size| by |size| map of pseudo-random risks, about as big as a real puzzle input.
+fn generated_map(size: usize) -> Grid<u8> {
+    let mut state: u32 = 0x2021_0015;
+    let rows = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| {
+                    state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    (state >> 24) as u8 % 9 + 1
+                })
+                .collect()
+        })
+        .collect();
+    Grid::from_rows(rows).unwrap()
+}
+
+/// Benches the heap, bucket queue and bidirectional searches on part 2's five-times tiled maps,
+/// and the first two on a map tiled a hundred times.
+fn bench_search(c: &mut Criterion) {
+    let maps = [
+        ("example", day15::parse_input(RISKS).unwrap()),
+        ("generated", generated_map(100)),
+    ];
+    for (name, map) in maps {
+        let tiled = RiskMap::new(map, 5);
+        let mut group = c.benchmark_group(format!("day15/{}", name));
+        group.bench_function("a_star", |b| b.iter(|| day15::a_star(black_box(&tiled))));
+        group.bench_function("dial", |b| b.iter(|| day15::dial(black_box(&tiled))));
+        group.bench_function("bidirectional", |b| {
+            b.iter(|| {
+                let goal = tiled.max_coord();
+                day15::bidirectional_between(black_box(&tiled), Coord::ORIGIN, goal, &VonNeumann)
+            })
+        });
+        group.finish();
+    }
+
+    // a million cells; a corner to corner search visits nearly all of them, so this measures
+    // throughput on a large map rather than the savings of keeping search state per tile
+    let tiled = RiskMap::new(day15::parse_input(RISKS).unwrap(), 100);
+    let mut group = c.benchmark_group("day15/example_x100");
+    group.sample_size(10);
+    group.bench_function("a_star", |b| b.iter(|| day15::a_star(black_box(&tiled))));
+    group.bench_function("dial", |b| b.iter(|| day15::dial(black_box(&tiled))));
+    group.finish();
+
+    // The same short route on the five times and hundred times tiled maps. Search state is only
+    // allocated for the few tiles near the route, so both should take about as long, where state
+    // for every cell would cost in proportion to the map.
+    let (start, goal) = (Coord::new(10, 10), Coord::new(30, 40));
+    let mut group = c.benchmark_group("day15/nearby");
+    for tiles in [5, 100] {
+        let tiled = RiskMap::new(day15::parse_input(RISKS).unwrap(), tiles);
+        group.bench_function(format!("a_star/x{}", tiles), |b| {
+            b.iter(|| day15::a_star_between(black_box(&tiled), start, &[goal], &VonNeumann))
+        });
+        group.bench_function(format!("dial/x{}", tiles), |b| {
+            b.iter(|| day15::dial_between(black_box(&tiled), start, &[goal], &VonNeumann))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
@@ -1,5 +1,15 @@
 use std::vec::Vec;
 
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, ParseError};
+use aoc_common::solution::Solution;
+
+pub fn parse_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    non_empty_lines(input)
+        .map(|line| line.parse_number(line.text.trim()))
+        .collect()
+}
+
 pub fn problem1(input: Vec<i32>) -> i32 {
     let mut increases = 0;
     for window in input.windows(2) {
@@ -18,14 +28,32 @@ pub fn problem2(input: Vec<i32>) -> i32 {
     problem1(window_sums)
 }
 
+pub struct Day1 {
+    depths: Vec<i32>,
+}
+
+impl Solution for Day1 {
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day1 {
+            depths: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<i32, AocError> {
+        Ok(problem1(self.depths.clone()))
+    }
+
+    fn part2(&self) -> Result<i32, AocError> {
+        Ok(problem2(self.depths.clone()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use std::path::Path;
-
-    const DATA_PATH: &'static str = "data/day1";
 
     #[test]
     fn problem1_example() {
@@ -34,26 +62,8 @@ mod tests {
     }
 
     #[test]
-    fn problem1_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-        let input = reader
-            .lines()
-            .map(|line| line.unwrap().parse::<i32>().unwrap())
-            .collect::<Vec<i32>>();
-
-        assert_eq!(problem1(input), 1532);
-    }
-
-    #[test]
-    fn problem2_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-        let input = reader
-            .lines()
-            .map(|line| line.unwrap().parse::<i32>().unwrap())
-            .collect::<Vec<i32>>();
-
-        assert_eq!(problem2(input), 1571);
+    fn parse_error() {
+        let err = parse_input("199\n200\n2O8\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
     }
 }
@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, ParseError};
+use aoc_common::solution::Solution;
 use itertools::Itertools;
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
@@ -48,7 +51,7 @@ impl Token {
     }
 }
 
-enum ParseError {
+enum SyntaxError {
     Corrupted(Token),
     Incomplete(Vec<Token>),
 }
@@ -57,7 +60,7 @@ fn parse_matching_end(
     expr: &[Token],
     expected_end: Token,
     next: &mut usize,
-) -> Result<(), ParseError> {
+) -> Result<(), SyntaxError> {
     match expr.get(*next) {
         Some(&end) if end == expected_end => {
             *next += 1;
@@ -67,33 +70,39 @@ fn parse_matching_end(
             parse_matching(expr, next)?;
             parse_matching_end(expr, expected_end, next)
         }
-        Some(&end) => Err(ParseError::Corrupted(end)),
-        None => Err(ParseError::Incomplete(vec![])),
+        Some(&end) => Err(SyntaxError::Corrupted(end)),
+        None => Err(SyntaxError::Incomplete(vec![])),
     }
 }
 
-fn parse_matching(expr: &[Token], next: &mut usize) -> Result<(), ParseError> {
+fn parse_matching(expr: &[Token], next: &mut usize) -> Result<(), SyntaxError> {
     let left = expr[*next];
     let expected_right = left.try_get_matching().unwrap();
     *next += 1;
     parse_expr(expr, next)
         .and_then(|_| parse_matching_end(expr, expected_right, next))
         .map_err(|err| match err {
-            ParseError::Incomplete(mut expected_list) => {
+            SyntaxError::Incomplete(mut expected_list) => {
                 expected_list.push(expected_right);
-                ParseError::Incomplete(expected_list)
+                SyntaxError::Incomplete(expected_list)
             }
             _ => err,
         })
 }
 
-fn parse_expr(expr: &[Token], next: &mut usize) -> Result<(), ParseError> {
+fn parse_expr(expr: &[Token], next: &mut usize) -> Result<(), SyntaxError> {
     match expr.get(*next) {
         Some(&token) if token.is_left() => parse_matching(expr, next),
         _ => Ok(()),
     }
 }
 
+pub fn parse_input(input: &str) -> Result<Vec<Vec<Token>>, ParseError> {
+    non_empty_lines(input)
+        .map(|line| line.map_chars(|c| Token::try_from(c).ok()))
+        .collect()
+}
+
 pub fn problem1(lines: Vec<Vec<Token>>) -> u32 {
     let error_values: HashMap<Token, u32> = [
         (Token::RAngle, 25137),
@@ -107,8 +116,8 @@ pub fn problem1(lines: Vec<Vec<Token>>) -> u32 {
         .iter()
         .filter_map(|line| match parse_expr(&line[..], &mut 0) {
             Ok(_) => None,
-            Err(ParseError::Incomplete(_)) => None,
-            Err(ParseError::Corrupted(actual_token)) => Some(actual_token),
+            Err(SyntaxError::Incomplete(_)) => None,
+            Err(SyntaxError::Corrupted(actual_token)) => Some(actual_token),
         })
         .into_group_map_by(|&expected| expected)
         .iter()
@@ -116,20 +125,20 @@ pub fn problem1(lines: Vec<Vec<Token>>) -> u32 {
         .sum()
 }
 
-pub fn problem2(lines: Vec<Vec<Token>>) -> u64 {
+pub fn problem2(lines: Vec<Vec<Token>>) -> Result<u64, AocError> {
     let mut incomplete_line_scores: Vec<u64> = lines
         .iter()
         .filter_map(|line| match parse_expr(&line[..], &mut 0) {
             Ok(_) => None,
-            Err(ParseError::Corrupted(_)) => None,
-            Err(ParseError::Incomplete(expected_tokens)) => {
+            Err(SyntaxError::Corrupted(_)) => None,
+            Err(SyntaxError::Incomplete(expected_tokens)) => {
                 let score = expected_tokens.iter().fold(0u64, |cur_score, &token| {
                     let token_value = match token {
                         Token::RParen => 1,
                         Token::RBracket => 2,
                         Token::RCurly => 3,
                         Token::RAngle => 4,
-                        _ => panic!(),
+                        _ => unreachable!("only closing tokens complete a line"),
                     };
                     (cur_score * 5) + token_value
                 });
@@ -138,21 +147,39 @@ pub fn problem2(lines: Vec<Vec<Token>>) -> u64 {
         })
         .collect();
     incomplete_line_scores.sort();
-    incomplete_line_scores[incomplete_line_scores.len() / 2]
+    incomplete_line_scores
+        .get(incomplete_line_scores.len() / 2)
+        .copied()
+        .ok_or_else(|| AocError::InvalidInput("no line is incomplete".to_owned()))
+}
+
+pub struct Day10 {
+    lines: Vec<Vec<Token>>,
+}
+
+impl Solution for Day10 {
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day10 {
+            lines: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<u32, AocError> {
+        Ok(problem1(self.lines.clone()))
+    }
+
+    fn part2(&self) -> Result<u64, AocError> {
+        problem2(self.lines.clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day10";
-
-    fn parse_input(input: &str) -> Vec<Vec<Token>> {
-        input
-            .split('\n')
-            .map(|line| line.trim().chars().map(|c| c.try_into().unwrap()).collect())
-            .collect()
-    }
     #[test]
     fn problem1_example() {
         let example = "\
@@ -166,18 +193,11 @@ mod tests {
 [<(<(<(<{}))><([]([]()
 <{([([[(<>()){}]>(<<{{
 <{([{{}}[<[[[<>{}]]]>[]]";
-        let lines = parse_input(example);
+        let lines = parse_input(example).unwrap();
 
         assert_eq!(problem1(lines), 26397);
     }
 
-    #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let lines = parse_input(&content);
-        assert_eq!(problem1(lines), 216297);
-    }
-
     #[test]
     fn problem2_example() {
         let example = "\
@@ -191,14 +211,13 @@ mod tests {
 [<(<(<(<{}))><([]([]()
 <{([([[(<>()){}]>(<<{{
 <{([{{}}[<[[[<>{}]]]>[]]";
-        let lines = parse_input(example);
-        assert_eq!(problem2(lines), 288957);
+        let lines = parse_input(example).unwrap();
+        assert_eq!(problem2(lines), Ok(288957));
     }
 
     #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let lines = parse_input(&content);
-        assert_eq!(problem2(lines), 2165057169);
+    fn problem2_no_incomplete_lines() {
+        let lines = parse_input("[]\n{()}\n").unwrap();
+        assert!(matches!(problem2(lines), Err(AocError::InvalidInput(_))));
     }
 }
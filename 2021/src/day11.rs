@@ -1,9 +1,14 @@
 use std::collections::VecDeque;
+use std::fmt::Display;
 use std::ops::AddAssign;
 
-use crate::coord::{Coord, CoordIterator};
+use aoc_common::coord::{Moore, Neighborhood};
+use aoc_common::error::AocError;
+use aoc_common::grid::Grid;
+use aoc_common::parse::{self, ParseError};
+use aoc_common::solution::Solution;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum EnergyLevel {
     Dormant(u8),
     Explosive,
@@ -18,106 +23,134 @@ impl AddAssign<u8> for EnergyLevel {
     }
 }
 
-impl From<char> for EnergyLevel {
-    fn from(c: char) -> Self {
-        let c_val = c.to_digit(10).unwrap() as u8;
-        assert!(c_val <= 9);
-        EnergyLevel::Dormant(c_val)
+impl TryFrom<char> for EnergyLevel {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c.to_digit(10) {
+            Some(c_val) => Ok(EnergyLevel::Dormant(c_val as u8)),
+            None => Err(()),
+        }
     }
 }
 
-pub fn problem1(input: &Vec<Vec<EnergyLevel>>, iterations: u32) -> u32 {
-    let mut flash_map = input.to_vec();
+/// Flashing octopuses are drawn as `*`.
+impl Display for EnergyLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnergyLevel::Explosive => write!(f, "*"),
+            EnergyLevel::Dormant(level) => write!(f, "{}", level),
+        }
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Grid<EnergyLevel>, ParseError> {
+    parse::grid(input, |c| EnergyLevel::try_from(c).ok())
+}
+
+/// Counts the flashes over |iterations| steps, where a flash energizes every octopus in its
+/// |neighborhood|.
+pub fn count_flashes(
+    input: &Grid<EnergyLevel>,
+    iterations: u32,
+    neighborhood: &impl Neighborhood,
+) -> u32 {
+    let mut flash_map = input.clone();
     let mut flashes = 0u32;
 
     for _ in 1..=iterations {
-        flashes += do_flash_step(&mut flash_map);
+        flashes += do_flash_step(&mut flash_map, neighborhood);
     }
 
     flashes
 }
 
-pub fn problem2(input: &Vec<Vec<EnergyLevel>>) -> u32 {
-    let mut flash_map = input.to_vec();
+/// Counts the steps until every octopus flashes at once.
+pub fn steps_until_synchronized(
+    input: &Grid<EnergyLevel>,
+    neighborhood: &impl Neighborhood,
+) -> u32 {
+    let mut flash_map = input.clone();
     let mut steps = 0u32;
 
-    while !flash_map
-        .iter()
-        .all(|row| row.iter().all(|el| *el == EnergyLevel::Dormant(0)))
-    {
-        do_flash_step(&mut flash_map);
+    while !flash_map.values().all(|el| *el == EnergyLevel::Dormant(0)) {
+        do_flash_step(&mut flash_map, neighborhood);
         steps += 1
     }
 
     steps
 }
 
-fn do_flash_step(map: &mut Vec<Vec<EnergyLevel>>) -> u32 {
+pub fn problem1(input: &Grid<EnergyLevel>, iterations: u32) -> u32 {
+    count_flashes(input, iterations, &Moore)
+}
+
+pub fn problem2(input: &Grid<EnergyLevel>) -> u32 {
+    steps_until_synchronized(input, &Moore)
+}
+
+fn do_flash_step(map: &mut Grid<EnergyLevel>, neighborhood: &impl Neighborhood) -> u32 {
     let mut q = VecDeque::new();
     let mut flashes = 0u32;
-    for row in 0..map.len() {
-        for col in 0..map[0].len() {
-            map[row][col] += 1;
-            if map[row][col] == EnergyLevel::Explosive {
-                flashes += 1;
-                q.push_back(Coord { row, col })
-            }
+    for coord in map.coords() {
+        map[coord] += 1;
+        if map[coord] == EnergyLevel::Explosive {
+            flashes += 1;
+            q.push_back(coord)
         }
     }
 
     while let Some(flash_center) = q.pop_front() {
-        for coord in map.surrounding_coords(flash_center) {
-            if map[coord.row][coord.col] == EnergyLevel::Explosive {
+        for coord in map.neighbors(neighborhood, flash_center) {
+            if map[coord] == EnergyLevel::Explosive {
                 continue;
             }
 
-            map[coord.row][coord.col] += 1;
-            if map[coord.row][coord.col] == EnergyLevel::Explosive {
+            map[coord] += 1;
+            if map[coord] == EnergyLevel::Explosive {
                 flashes += 1;
                 q.push_back(coord)
             }
         }
     }
 
-    for row in 0..map.len() {
-        for col in 0..map[0].len() {
-            if map[row][col] == EnergyLevel::Explosive {
-                map[row][col] = EnergyLevel::Dormant(0);
-            }
+    for level in map.values_mut() {
+        if *level == EnergyLevel::Explosive {
+            *level = EnergyLevel::Dormant(0);
         }
     }
 
     flashes
 }
 
-#[allow(dead_code)]
-fn stringify_flash_map(flash_map: &Vec<Vec<EnergyLevel>>) -> String {
-    let mut printed = String::new();
-    for row in 0..flash_map.len() {
-        for col in 0..flash_map[0].len() {
-            match flash_map[row][col] {
-                EnergyLevel::Explosive => printed.push_str("*"),
-                EnergyLevel::Dormant(level) => printed.push_str(format!("{}", level).as_str()),
-            }
-        }
-        printed.push('\n');
+pub struct Day11 {
+    energy_levels: Grid<EnergyLevel>,
+}
+
+impl Solution for Day11 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day11 {
+            energy_levels: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<u32, AocError> {
+        Ok(problem1(&self.energy_levels, 100))
     }
 
-    printed
+    fn part2(&self) -> Result<u32, AocError> {
+        Ok(problem2(&self.energy_levels))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
-    const DATA_PATH: &'static str = "data/day11";
+    use aoc_common::coord::VonNeumann;
 
-    fn parse_input(input: &str) -> Vec<Vec<EnergyLevel>> {
-        input
-            .split('\n')
-            .map(|line| line.trim().chars().map(|c| c.into()).collect())
-            .collect()
-    }
+    use super::*;
 
     #[test]
     fn problem1_example() {
@@ -132,17 +165,24 @@ mod tests {
 6882881134
 4846848554
 5283751526";
-        let energy_levels = parse_input(example);
+        let energy_levels = parse_input(example).unwrap();
 
         assert_eq!(problem1(&energy_levels, 10), 204);
         assert_eq!(problem1(&energy_levels, 100), 1656);
     }
 
     #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let flash_map = parse_input(&content);
-        assert_eq!(problem1(&flash_map, 100), 1637);
+    fn flash_step() {
+        let mut flash_map = parse_input("11111\n19991\n19191\n19991\n11111").unwrap();
+        assert_eq!(do_flash_step(&mut flash_map, &Moore), 9);
+        assert_eq!(flash_map.to_string(), "34543\n40004\n50005\n40004\n34543\n");
+    }
+
+    #[test]
+    fn flash_step_von_neumann() {
+        let mut flash_map = parse_input("11111\n19991\n19191\n19991\n11111").unwrap();
+        assert_eq!(do_flash_step(&mut flash_map, &VonNeumann), 8);
+        assert_eq!(flash_map.to_string(), "23332\n30003\n30603\n30003\n23332\n");
     }
 
     #[test]
@@ -158,15 +198,8 @@ mod tests {
 6882881134
 4846848554
 5283751526";
-        let energy_levels = parse_input(example);
+        let energy_levels = parse_input(example).unwrap();
 
         assert_eq!(problem2(&energy_levels), 195);
     }
-
-    #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let flash_map = parse_input(&content);
-        assert_eq!(problem2(&flash_map), 242);
-    }
 }
@@ -1,8 +1,14 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{BTreeSet, HashMap},
     fmt::Debug,
+    str::FromStr,
 };
 
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, Line, ParseError, ParseErrorKind};
+use aoc_common::search::{self, Graph};
+use aoc_common::solution::Solution;
+
 #[derive(Hash, PartialEq, Eq, Clone, Copy)]
 pub enum CaveNode {
     Start,
@@ -34,74 +40,101 @@ impl Debug for CaveNode {
     }
 }
 
-impl From<&str> for CaveNode {
-    fn from(s: &str) -> Self {
-        match s.trim() {
-            "start" => CaveNode::Start,
-            "end" => CaveNode::End,
-            v if v.chars().next().unwrap().is_ascii_lowercase() => {
-                CaveNode::Small(u16::from_str_radix(v, 36).unwrap())
-            }
-            v => CaveNode::Big(u16::from_str_radix(v, 36).unwrap()),
+impl FromStr for CaveNode {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line { number: 1, text: s };
+        let invalid = || line.error_at(s, ParseErrorKind::Expected("a cave name"));
+        match s {
+            "start" => Ok(CaveNode::Start),
+            "end" => Ok(CaveNode::End),
+            v if v.chars().all(|c| c.is_ascii_lowercase()) => u16::from_str_radix(v, 36)
+                .map(CaveNode::Small)
+                .map_err(|_| invalid()),
+            v if v.chars().all(|c| c.is_ascii_uppercase()) => u16::from_str_radix(v, 36)
+                .map(CaveNode::Big)
+                .map_err(|_| invalid()),
+            _ => Err(invalid()),
         }
     }
 }
 
-#[derive(Debug)]
-struct CavePath {
-    path: Vec<CaveNode>,
-    visited_small_nodes: HashSet<u16>,
-    duplicate_small_node: Option<u16>,
+/// A point in a walk through the caves: the cave the walk is in, the small caves it has been
+/// through, and whether it has been through one of them twice.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CaveState {
+    cave: CaveNode,
+    visited_small_caves: BTreeSet<u16>,
+    revisited_small_cave: bool,
 }
 
-impl CavePath {
-    fn current(&self) -> &CaveNode {
-        self.path.last().unwrap()
-    }
-
+impl CaveState {
     fn append(&self, next_node: &CaveNode, allow_duplicate_small_node: bool) -> Option<Self> {
         match next_node {
             CaveNode::Small(id)
-                if self.visited_small_nodes.contains(id)
+                if self.visited_small_caves.contains(id)
                     && allow_duplicate_small_node
-                    && self.duplicate_small_node.is_none() =>
+                    && !self.revisited_small_cave =>
             {
                 Some(Self {
-                    path: self.path.iter().copied().chain([*next_node]).collect(),
-                    visited_small_nodes: self
-                        .visited_small_nodes
-                        .iter()
-                        .copied()
-                        .chain([*id])
-                        .collect(),
-                    duplicate_small_node: Some(*id),
+                    cave: *next_node,
+                    visited_small_caves: self.visited_small_caves.clone(),
+                    revisited_small_cave: true,
+                })
+            }
+            CaveNode::Small(id) if !self.visited_small_caves.contains(id) => {
+                let mut visited_small_caves = self.visited_small_caves.clone();
+                visited_small_caves.insert(*id);
+                Some(Self {
+                    cave: *next_node,
+                    visited_small_caves,
+                    revisited_small_cave: self.revisited_small_cave,
                 })
             }
-            CaveNode::Small(id) if !self.visited_small_nodes.contains(id) => Some(Self {
-                path: self.path.iter().copied().chain([*next_node]).collect(),
-                visited_small_nodes: self
-                    .visited_small_nodes
-                    .iter()
-                    .copied()
-                    .chain([*id])
-                    .collect(),
-                duplicate_small_node: self.duplicate_small_node,
-            }),
             CaveNode::Big(_) | CaveNode::End => Some(Self {
-                path: self.path.iter().copied().chain([*next_node]).collect(),
-                visited_small_nodes: self.visited_small_nodes.clone(),
-                duplicate_small_node: self.duplicate_small_node,
+                cave: *next_node,
+                visited_small_caves: self.visited_small_caves.clone(),
+                revisited_small_cave: self.revisited_small_cave,
             }),
             _ => None,
         }
     }
 }
 
+/// The walks through the caves allowed by the puzzle rules, as a graph between |CaveState|s.
+struct CaveSystem {
+    connections: HashMap<CaveNode, Vec<CaveNode>>,
+    allow_duplicate_small_node: bool,
+}
+
+impl Graph for CaveSystem {
+    type Node = CaveState;
+    type Cost = u32;
+
+    fn successors(&self, state: &CaveState) -> impl Iterator<Item = (CaveState, u32)> {
+        self.connections
+            .get(&state.cave)
+            .into_iter()
+            .flatten()
+            .filter_map(move |next_node| state.append(next_node, self.allow_duplicate_small_node))
+            .map(|next_state| (next_state, 1))
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<(CaveNode, CaveNode)>, ParseError> {
+    non_empty_lines(input)
+        .map(|line| {
+            let (src, dest) = line.split_once("-", "`-`")?;
+            Ok((line.parse(src.trim())?, line.parse(dest.trim())?))
+        })
+        .collect()
+}
+
 pub fn count_paths(
     connections: &Vec<(CaveNode, CaveNode)>,
     allow_duplicate_small_node: bool,
-) -> u32 {
-    let mut paths = 0u32;
+) -> Result<u64, AocError> {
     let mut connections_map = HashMap::new();
 
     for (start, end) in connections {
@@ -131,34 +164,50 @@ pub fn count_paths(
         }
     }
 
-    let mut q = VecDeque::new();
-    q.push_back(CavePath {
-        path: vec![CaveNode::Start],
-        visited_small_nodes: HashSet::new(),
-        duplicate_small_node: None,
-    });
-
-    while let Some(path) = q.pop_front() {
-        match *path.current() {
-            CaveNode::End => paths += 1,
-            current if connections_map.contains_key(&current) => q.extend(
-                connections_map[&current]
-                    .iter()
-                    .filter_map(|next_node| path.append(next_node, allow_duplicate_small_node)),
-            ),
-            _ => (),
-        }
+    let caves = CaveSystem {
+        connections: connections_map,
+        allow_duplicate_small_node,
+    };
+    let start = CaveState {
+        cave: CaveNode::Start,
+        visited_small_caves: BTreeSet::new(),
+        revisited_small_cave: false,
+    };
+    search::count_paths(&caves, start, |state| state.cave == CaveNode::End).ok_or_else(|| {
+        AocError::InvalidInput(
+            "two big caves are connected, so there are infinitely many paths".to_owned(),
+        )
+    })
+}
+
+pub struct Day12 {
+    connections: Vec<(CaveNode, CaveNode)>,
+}
+
+impl Solution for Day12 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day12 {
+            connections: parse_input(input)?,
+        })
     }
 
-    paths
+    fn part1(&self) -> Result<u64, AocError> {
+        count_paths(&self.connections, false)
+    }
+
+    fn part2(&self) -> Result<u64, AocError> {
+        count_paths(&self.connections, true)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day12";
-    const EXAMPLE_1: &'static str = "\
+    const EXAMPLE_1: &str = "\
 start-A
 start-b
 A-c
@@ -166,7 +215,7 @@ A-b
 b-d
 A-end
 b-end";
-    const EXAMPLE_2: &'static str = "\
+    const EXAMPLE_2: &str = "\
 dc-end
 HN-start
 start-kj
@@ -177,7 +226,7 @@ HN-end
 kj-sa
 kj-HN
 kj-dc";
-    const EXAMPLE_3: &'static str = "\
+    const EXAMPLE_3: &str = "\
 fs-end
 he-DX
 fs-he
@@ -197,61 +246,57 @@ zg-he
 pj-fs
 start-RW";
 
-    fn parse_input(input: &str) -> Vec<(CaveNode, CaveNode)> {
-        input
-            .split('\n')
-            .map(|line| {
-                let mut split_line = line.split('-');
-                let src = split_line.next().unwrap();
-                let dest = split_line.next().unwrap();
-                assert_eq!(split_line.next(), None);
-
-                (src.into(), dest.into())
-            })
-            .collect()
+    #[test]
+    fn parse_error() {
+        let err = parse_input("start-A\nA-b\nb-c3\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
+
+        let err = parse_input("start-A\nA b\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
     }
 
     #[test]
     fn problem1_example1() {
-        assert_eq!(count_paths(&parse_input(EXAMPLE_1), false), 10);
+        assert_eq!(count_paths(&parse_input(EXAMPLE_1).unwrap(), false), Ok(10));
     }
 
     #[test]
     fn problem1_example2() {
-        assert_eq!(count_paths(&parse_input(EXAMPLE_2), false), 19);
+        assert_eq!(count_paths(&parse_input(EXAMPLE_2).unwrap(), false), Ok(19));
     }
 
     #[test]
     fn problem1_example3() {
-        assert_eq!(count_paths(&parse_input(EXAMPLE_3), false), 226);
-    }
-
-    #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let connections = parse_input(&content);
-        assert_eq!(count_paths(&connections, false), 3563);
+        assert_eq!(
+            count_paths(&parse_input(EXAMPLE_3).unwrap(), false),
+            Ok(226)
+        );
     }
 
     #[test]
     fn problem2_example1() {
-        assert_eq!(count_paths(&parse_input(EXAMPLE_1), true), 36);
+        assert_eq!(count_paths(&parse_input(EXAMPLE_1).unwrap(), true), Ok(36));
     }
 
     #[test]
     fn problem2_example2() {
-        assert_eq!(count_paths(&parse_input(EXAMPLE_2), true), 103);
+        assert_eq!(count_paths(&parse_input(EXAMPLE_2).unwrap(), true), Ok(103));
     }
 
     #[test]
     fn problem2_example3() {
-        assert_eq!(count_paths(&parse_input(EXAMPLE_3), true), 3509);
+        assert_eq!(
+            count_paths(&parse_input(EXAMPLE_3).unwrap(), true),
+            Ok(3509)
+        );
     }
 
     #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let connections = parse_input(&content);
-        assert_eq!(count_paths(&connections, true), 105453);
+    fn connected_big_caves() {
+        let connections = parse_input("start-A\nA-B\nB-end").unwrap();
+        assert!(matches!(
+            count_paths(&connections, false),
+            Err(AocError::InvalidInput(_))
+        ));
     }
 }
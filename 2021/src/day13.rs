@@ -1,18 +1,17 @@
 use std::collections::HashSet;
 
+use aoc_common::coord::Coord;
+use aoc_common::error::AocError;
+use aoc_common::parse::{lines, Line, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
+
 #[derive(Debug)]
 pub enum Fold {
-    X(usize),
-    Y(usize),
-}
-
-#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, PartialOrd, Ord)]
-pub struct Coord {
-    y: usize,
-    x: usize,
+    X(i32),
+    Y(i32),
 }
 
-fn fold_index(index: usize, fold: usize) -> usize {
+fn fold_index(index: i32, fold: i32) -> i32 {
     if index > fold {
         let offset = index % (fold + 1);
         fold - offset - 1
@@ -21,34 +20,66 @@ fn fold_index(index: usize, fold: usize) -> usize {
     }
 }
 
-impl Coord {
-    pub fn fold(&self, folds: &[Fold]) -> Self {
-        folds.iter().fold(*self, |coord, fold| match fold {
-            Fold::X(index) => Coord {
-                x: fold_index(coord.x, *index),
-                y: coord.y,
-            },
-            Fold::Y(index) => Coord {
-                x: coord.x,
-                y: fold_index(coord.y, *index),
-            },
-        })
+fn fold_dot(dot: &Coord, folds: &[Fold]) -> Coord {
+    folds.iter().fold(*dot, |coord, fold| match fold {
+        Fold::X(index) => Coord::from_xy(fold_index(coord.x(), *index), coord.y()),
+        Fold::Y(index) => Coord::from_xy(coord.x(), fold_index(coord.y(), *index)),
+    })
+}
+
+const FOLD_PREFIX: &str = "fold along ";
+
+fn parse_fold(line: &Line) -> Result<Fold, ParseError> {
+    let text = line.text.trim();
+    let instruction = text
+        .strip_prefix(FOLD_PREFIX)
+        .ok_or_else(|| line.error_at(text, ParseErrorKind::Expected("`fold along`")))?;
+    let (axis, index) = instruction
+        .split_once('=')
+        .ok_or_else(|| line.error_at_end(ParseErrorKind::Expected("`=`")))?;
+    match axis {
+        "x" => Ok(Fold::X(line.parse_number(index)?)),
+        "y" => Ok(Fold::Y(line.parse_number(index)?)),
+        _ => Err(line.error_at(axis, ParseErrorKind::Expected("x or y"))),
     }
 }
 
-pub fn count_dots(dot_list: &Vec<Coord>, folds: &[Fold]) -> usize {
+pub fn parse_input(input: &str) -> Result<(Vec<Coord>, Vec<Fold>), ParseError> {
+    let mut coords = Vec::new();
+    let mut folds = Vec::new();
+    let mut lines = lines(input);
+
+    // dots come first, followed by a blank line and then the fold instructions
+    for line in lines.by_ref() {
+        let text = line.text.trim();
+        if text.is_empty() {
+            break;
+        }
+
+        let (x, y) = line.split_once(",", "`,`")?;
+        coords.push(Coord::from_xy(line.parse_number(x)?, line.parse_number(y)?));
+    }
+
+    for line in lines.filter(|line| !line.text.trim().is_empty()) {
+        folds.push(parse_fold(&line)?);
+    }
+
+    Ok((coords, folds))
+}
+
+pub fn count_dots(dot_list: &[Coord], folds: &[Fold]) -> usize {
     dot_list
         .iter()
-        .map(|dot| dot.fold(folds))
+        .map(|dot| fold_dot(dot, folds))
         .collect::<HashSet<Coord>>()
         .len()
 }
 
-pub fn decode_dots(dot_list: &Vec<Coord>, folds: &[Fold]) -> String {
+pub fn decode_dots(dot_list: &[Coord], folds: &[Fold]) -> String {
     let mut ret = String::new();
     let mut folded: Vec<_> = dot_list
         .iter()
-        .map(|dot| dot.fold(folds))
+        .map(|dot| fold_dot(dot, folds))
         .collect::<HashSet<Coord>>()
         .into_iter()
         .collect();
@@ -57,27 +88,49 @@ pub fn decode_dots(dot_list: &Vec<Coord>, folds: &[Fold]) -> String {
     let mut x_cursor = 0;
     let mut y_cursor = 0;
     for coord in folded.iter() {
-        if coord.y > 0 && y_cursor < coord.y {
+        if coord.y() > 0 && y_cursor < coord.y() {
             ret.push('\n');
             x_cursor = 0;
         }
-        y_cursor = coord.y;
-        for _ in x_cursor..coord.x {
+        y_cursor = coord.y();
+        for _ in x_cursor..coord.x() {
             ret.push(' ');
         }
         ret.push('X');
-        x_cursor = coord.x + 1;
+        x_cursor = coord.x() + 1;
     }
 
     ret
 }
 
+pub struct Day13 {
+    dots: Vec<Coord>,
+    folds: Vec<Fold>,
+}
+
+impl Solution for Day13 {
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        let (dots, folds) = parse_input(input)?;
+        Ok(Day13 { dots, folds })
+    }
+
+    fn part1(&self) -> Result<usize, AocError> {
+        Ok(count_dots(&self.dots, &self.folds[0..1]))
+    }
+
+    fn part2(&self) -> Result<String, AocError> {
+        Ok(decode_dots(&self.dots, &self.folds))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day13";
-    const EXAMPLE_1: &'static str = "\
+    const EXAMPLE_1: &str = "\
 6,10
 0,14
 9,10
@@ -99,61 +152,25 @@ mod tests {
 
 fold along y=7
 fold along x=5";
-    const FOLD_PREFIX: &'static str = "fold along ";
-
-    fn parse_input(input: &str) -> (Vec<Coord>, Vec<Fold>) {
-        let coords: Vec<_> = input
-            .lines()
-            .take_while(|line| line.trim().len() != 0)
-            .map(|line| {
-                let mut split = line.split(',').map(|index| index.parse::<usize>().unwrap());
-                Coord {
-                    x: split.next().unwrap(),
-                    y: split.next().unwrap(),
-                }
-            })
-            .collect();
-
-        let folds = input.lines().skip(coords.len() + 1).map(|line| {
-            match line.chars().nth(FOLD_PREFIX.len()).unwrap() {
-                'x' => Fold::X(line.split('=').nth(1).unwrap().parse().unwrap()),
-                'y' => Fold::Y(line.split('=').nth(1).unwrap().parse().unwrap()),
-                _ => panic!(),
-            }
-        });
-
-        (coords, folds.collect())
+
+    #[test]
+    fn parse_error() {
+        let err = parse_input("6,10\n0,14\n\nfold along y=7\nfold along z=5").unwrap_err();
+        assert_eq!((err.line, err.column), (5, 12));
+
+        let err = parse_input("6,10\n0;14\n\nfold along y=7").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
     }
 
     #[test]
     fn problem1_example1() {
-        let (coords, folds) = parse_input(EXAMPLE_1);
+        let (coords, folds) = parse_input(EXAMPLE_1).unwrap();
         assert_eq!(count_dots(&coords, &folds[0..1]), 17);
     }
 
     #[test]
     fn problem1_example2() {
-        let (coords, folds) = parse_input(EXAMPLE_1);
+        let (coords, folds) = parse_input(EXAMPLE_1).unwrap();
         assert_eq!(count_dots(&coords, &folds[..]), 16);
     }
-
-    #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let (coords, folds) = parse_input(&content);
-        assert_eq!(count_dots(&coords, &folds[0..1]), 842);
-    }
-
-    #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let (coords, folds) = parse_input(&content);
-        assert_eq!(decode_dots(&coords, &folds[..]), "\
-XXX  XXXX X  X XXX   XX    XX XXXX X  X
-X  X X    X X  X  X X  X    X    X X  X
-XXX  XXX  XX   X  X X       X   X  X  X
-X  X X    X X  XXX  X       X  X   X  X
-X  X X    X X  X X  X  X X  X X    X  X
-XXX  X    X  X X  X  XX   XX  XXXX  XX");
-    }
 }
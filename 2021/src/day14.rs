@@ -1,8 +1,13 @@
 use std::{collections::HashMap, fmt::Display};
 
+use aoc_common::error::AocError;
+use aoc_common::parse::{lines, Line, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
 use itertools::{Itertools, MinMaxResult};
 
-#[derive(Hash, PartialEq, Eq, Clone, Copy)]
+use crate::error::PuzzleError;
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
 pub struct Pair(char, char);
 
 impl Display for Pair {
@@ -16,16 +21,68 @@ impl Display for Pair {
     }
 }
 
-const fn char_index(c: &char) -> usize {
-    (*c as u8 - 'A' as u8) as usize
+/// The index of element |c| in a table of counts, failing unless it is an uppercase letter.
+fn char_index(c: char) -> Result<usize, AocError> {
+    if c.is_ascii_uppercase() {
+        Ok((c as u8 - b'A') as usize)
+    } else {
+        Err(AocError::InvalidInput(format!("{:?} is not an element", c)))
+    }
 }
 
-pub fn expand_and_count(template: &Vec<char>, rules: &HashMap<Pair, char>, iterations: u8) -> u64 {
+/// Adds |more| to |count|, failing if the total doesn't fit in 64 bits.
+fn add_count(count: &mut u64, more: u64) -> Result<(), AocError> {
+    *count = count
+        .checked_add(more)
+        .ok_or_else(|| AocError::InvalidInput("element counts overflow 64 bits".to_owned()))?;
+    Ok(())
+}
+
+fn parse_elements(line: &Line, token: &str) -> Result<Vec<char>, ParseError> {
+    match token.chars().find(|c| !c.is_ascii_uppercase()) {
+        Some(c) => {
+            let offset = token.find(c).unwrap();
+            Err(line.error_at(&token[offset..], ParseErrorKind::UnexpectedChar(c)))
+        }
+        None => Ok(token.chars().collect()),
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<(Vec<char>, HashMap<Pair, char>), ParseError> {
+    let mut rules = HashMap::new();
+    let mut lines = lines(input);
+    let template_line = lines.next().ok_or_else(|| ParseError::end_of(input))?;
+    let template = parse_elements(&template_line, template_line.text.trim())?;
+
+    for line in lines.filter(|line| !line.text.trim().is_empty()) {
+        let (pair_str, insertion) = line.split_once(" -> ", "`->`")?;
+        let pair = match parse_elements(&line, pair_str)?[..] {
+            [first, second] => Pair(first, second),
+            _ => {
+                return Err(line.error_at(pair_str, ParseErrorKind::Expected("a pair of elements")))
+            }
+        };
+        let insertion = match parse_elements(&line, insertion.trim())?[..] {
+            [element] => element,
+            _ => return Err(line.error_at(insertion, ParseErrorKind::Expected("a single element"))),
+        };
+
+        rules.insert(pair, insertion);
+    }
+
+    Ok((template, rules))
+}
+
+pub fn expand_and_count(
+    template: &[char],
+    rules: &HashMap<Pair, char>,
+    iterations: u8,
+) -> Result<u64, AocError> {
     let mut letter_counts = [0u64; 26];
     let mut pair_counts = HashMap::new();
 
     for letter in template.iter() {
-        letter_counts[char_index(letter)] += 1;
+        letter_counts[char_index(*letter)?] += 1;
     }
 
     for pair in template.windows(2).map(|window| Pair(window[0], window[1])) {
@@ -34,14 +91,26 @@ pub fn expand_and_count(template: &Vec<char>, rules: &HashMap<Pair, char>, itera
 
     for _ in 0..iterations {
         let current_pairs = pair_counts.clone();
-        for (pair, count) in current_pairs {
-            let insertion = rules[&pair];
+        // pairs that no longer occur don't need a rule
+        for (pair, count) in current_pairs.into_iter().filter(|(_, count)| *count > 0) {
+            let insertion = *rules.get(&pair).ok_or_else(|| {
+                PuzzleError::MissingRule {
+                    pair: [pair.0, pair.1],
+                }
+                .on_day(14)
+            })?;
 
-            letter_counts[char_index(&insertion)] += count;
+            add_count(&mut letter_counts[char_index(insertion)?], count)?;
 
             *pair_counts.get_mut(&pair).unwrap() -= count;
-            *pair_counts.entry(Pair(pair.0, insertion)).or_insert(0) += count;
-            *pair_counts.entry(Pair(insertion, pair.1)).or_insert(0) += count;
+            add_count(
+                pair_counts.entry(Pair(pair.0, insertion)).or_insert(0),
+                count,
+            )?;
+            add_count(
+                pair_counts.entry(Pair(insertion, pair.1)).or_insert(0),
+                count,
+            )?;
         }
     }
 
@@ -50,8 +119,32 @@ pub fn expand_and_count(template: &Vec<char>, rules: &HashMap<Pair, char>, itera
         .filter(|count| *count > 0)
         .minmax()
     {
-        MinMaxResult::MinMax(min, max) => max - min,
-        _ => panic!(),
+        MinMaxResult::MinMax(min, max) => Ok(max - min),
+        MinMaxResult::OneElement(_) => Ok(0),
+        MinMaxResult::NoElements => Err(AocError::EmptyInput),
+    }
+}
+
+pub struct Day14 {
+    template: Vec<char>,
+    rules: HashMap<Pair, char>,
+}
+
+impl Solution for Day14 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        let (template, rules) = parse_input(input)?;
+        Ok(Day14 { template, rules })
+    }
+
+    fn part1(&self) -> Result<u64, AocError> {
+        expand_and_count(&self.template, &self.rules, 10)
+    }
+
+    fn part2(&self) -> Result<u64, AocError> {
+        expand_and_count(&self.template, &self.rules, 40)
     }
 }
 
@@ -59,8 +152,7 @@ pub fn expand_and_count(template: &Vec<char>, rules: &HashMap<Pair, char>, itera
 mod tests {
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day14";
-    const EXAMPLE_1: &'static str = "\
+    const EXAMPLE_1: &str = "\
 NNCB
 
 CH -> B
@@ -80,55 +172,57 @@ BC -> B
 CC -> N
 CN -> C";
 
-    fn parse_input(input: &str) -> (Vec<char>, HashMap<Pair, char>) {
-        let mut rules = HashMap::new();
-        let mut lines = input.lines();
-        let template = lines.next().unwrap();
-        lines.next().unwrap();
-        for rule in lines {
-            let mut split = rule.split(" -> ");
-
-            let pair_str = split.next().unwrap();
-            assert_eq!(pair_str.len(), 2);
-            let mut pair_chars = pair_str.chars();
-
-            let insertion = split.next().unwrap();
-            assert_eq!(insertion.len(), 1);
-
-            assert_eq!(split.next(), None);
+    #[test]
+    fn parse_error() {
+        let err = parse_input("NNCB\n\nCH -> B\nHh -> N").unwrap_err();
+        assert_eq!((err.line, err.column), (4, 2));
 
-            rules.insert(
-                Pair(pair_chars.next().unwrap(), pair_chars.next().unwrap()),
-                insertion.chars().next().unwrap(),
-            );
-        }
+        let err = parse_input("NNCB\n\nCH -> B\nHHH -> N").unwrap_err();
+        assert_eq!((err.line, err.column), (4, 1));
+    }
 
-        (template.chars().collect(), rules)
+    #[test]
+    fn missing_rule() {
+        let (template, rules) = parse_input("NNC\n\nNN -> C\n").unwrap();
+        assert_eq!(
+            expand_and_count(&template, &rules, 1),
+            Err(PuzzleError::MissingRule { pair: ['N', 'C'] }.on_day(14))
+        );
     }
 
     #[test]
-    fn problem1_example1() {
-        let (template, rules) = parse_input(EXAMPLE_1);
-        assert_eq!(expand_and_count(&template, &rules, 10), 1588);
+    fn invalid_elements() {
+        let rules = HashMap::from([(Pair('N', 'N'), 'c')]);
+        assert!(matches!(
+            expand_and_count(&['N', 'N'], &rules, 1),
+            Err(AocError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            expand_and_count(&['N', '$'], &HashMap::new(), 0),
+            Err(AocError::InvalidInput(_))
+        ));
     }
 
     #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let (template, rules) = parse_input(&content);
-        assert_eq!(expand_and_count(&template, &rules, 10), 3230);
+    fn count_overflow() {
+        // every step doubles the count of |A|, which passes 64 bits after 64 steps
+        let (template, rules) = parse_input("AA\n\nAA -> A\n").unwrap();
+        assert_eq!(expand_and_count(&template, &rules, 62), Ok(0));
+        assert!(matches!(
+            expand_and_count(&template, &rules, 64),
+            Err(AocError::InvalidInput(_))
+        ));
     }
 
     #[test]
-    fn problem2_example1() {
-        let (template, rules) = parse_input(EXAMPLE_1);
-        assert_eq!(expand_and_count(&template, &rules, 40), 2188189693529);
+    fn problem1_example1() {
+        let (template, rules) = parse_input(EXAMPLE_1).unwrap();
+        assert_eq!(expand_and_count(&template, &rules, 10), Ok(1588));
     }
 
     #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let (template, rules) = parse_input(&content);
-        assert_eq!(expand_and_count(&template, &rules, 40), 3542388214529);
+    fn problem2_example1() {
+        let (template, rules) = parse_input(EXAMPLE_1).unwrap();
+        assert_eq!(expand_and_count(&template, &rules, 40), Ok(2188189693529));
     }
 }
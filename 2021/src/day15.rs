@@ -1,117 +1,442 @@
-use std::collections::BinaryHeap;
+use std::collections::HashMap;
 
-use crate::coord::{Coord, Coords};
+use aoc_common::coord::{Coord, Neighborhood, VonNeumann};
+use aoc_common::error::AocError;
+use aoc_common::grid::Grid;
+use aoc_common::parse::{self, ParseError};
+use aoc_common::search::{self, Graph, Heuristic, LpaStar, NodeMap, Path, ReversibleGraph};
+use aoc_common::solution::Solution;
 
-struct OpenSetValue {
-    coord: Coord,
-    estimated_cost: f64,
+pub struct RiskMap {
+    map: Grid<u8>,
+    max: Coord,
+    rows: usize,
+    cols: usize,
+    /// Cells whose risk was set after tiling.
+    edits: HashMap<Coord, u8>,
 }
 
-impl PartialEq for OpenSetValue {
-    fn eq(&self, other: &Self) -> bool {
-        self.coord == other.coord && self.estimated_cost == other.estimated_cost
+impl RiskMap {
+    pub fn new(map: Grid<u8>, tiles: usize) -> Self {
+        assert!(tiles > 0);
+        assert!(!map.is_empty());
+
+        let rows = map.height();
+        let cols = map.width();
+        let max = Coord::from_indices((rows * tiles) - 1, (cols * tiles) - 1);
+        Self {
+            map,
+            max,
+            rows,
+            cols,
+            edits: HashMap::new(),
+        }
+    }
+
+    /// The bottom right cell of the tiled map.
+    pub fn max_coord(&self) -> Coord {
+        self.max
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.to_indices().is_some() && coord.row <= self.max.row && coord.col <= self.max.col
+    }
+
+    /// Risks go up by one for each tile right or down from the top left, wrapping from 9
+    /// back to 1.
+    pub fn get(&self, coord: &Coord) -> u8 {
+        if !self.edits.is_empty() {
+            if let Some(risk) = self.edits.get(coord) {
+                return *risk;
+            }
+        }
+
+        let (row, col) = coord.to_indices().expect("coordinate is on the map");
+        let real_row = row % self.rows;
+        let real_col = col % self.cols;
+        let shift = (row / self.rows) + (col / self.cols);
+        let real_value = self.map[Coord::from_indices(real_row, real_col)] as usize;
+        ((real_value - 1 + shift) % 9 + 1) as u8
     }
 }
 
-impl Eq for OpenSetValue {}
+impl RiskMap {
+    /// Sets the risk of a single cell, leaving the same cell in other tiles alone.
+    pub fn set(&mut self, coord: Coord, risk: u8) {
+        assert!(self.contains(coord), "{:?} is not on the map", coord);
+        assert!(
+            (1..=9).contains(&risk),
+            "risk {} is not between 1 and 9",
+            risk
+        );
+        self.edits.insert(coord, risk);
+    }
+}
 
-impl PartialOrd for OpenSetValue {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        // we want reverse ordering for open_set values -- that is, we want
-        // ourselves to return |Ordering::Greater| if our estimated cost is less
-        // than |other|'s. This is to work with the default BinaryHeap behavior,
-        // which is a max-heap rather than a min-heap
-        other.estimated_cost.partial_cmp(&self.estimated_cost)
+pub fn parse_input(input: &str) -> Result<Grid<u8>, ParseError> {
+    parse::grid(input, |c| match c {
+        '1'..='9' => c.to_digit(10).map(|risk| risk as u8),
+        _ => None,
+    })
+}
+
+/// A lowest-risk route through a |RiskMap|. The start is the first coordinate of |path|, and
+/// isn't counted in |risk| since it is never entered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub risk: u32,
+    pub path: Vec<Coord>,
+}
+
+impl From<Path<Coord, u32>> for Route {
+    fn from(path: Path<Coord, u32>) -> Self {
+        Route {
+            risk: path.cost,
+            path: path.nodes,
+        }
     }
 }
 
-impl Ord for OpenSetValue {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.partial_cmp(self).unwrap()
+pub fn a_star(map: &RiskMap) -> Option<Route> {
+    a_star_in(map, &VonNeumann)
+}
+
+/// The same search as |a_star|, using Dial's bucket queue instead of a binary heap.
+pub fn dial(map: &RiskMap) -> Option<Route> {
+    dial_between(map, Coord::ORIGIN, &[map.max], &VonNeumann)
+}
+
+/// Moving between neighboring cells of a |RiskMap|, where entering a cell costs its risk.
+struct RiskGraph<'a, N> {
+    map: &'a RiskMap,
+    neighborhood: &'a N,
+}
+
+impl<N: Neighborhood> Graph for RiskGraph<'_, N> {
+    type Node = Coord;
+    type Cost = u32;
+
+    fn successors(&self, coord: &Coord) -> impl Iterator<Item = (Coord, u32)> {
+        let map = self.map;
+        self.neighborhood
+            .neighbors(map.max, *coord)
+            .map(move |neighbor| (neighbor, map.get(&neighbor) as u32))
     }
 }
 
-const ORIGIN: Coord = Coord { row: 0, col: 0 };
+/// Entering a cell costs the same from all of its neighbors, so for a symmetric neighborhood
+/// the cells a cell can be entered from are its neighbors.
+impl<N: Neighborhood> ReversibleGraph for RiskGraph<'_, N> {
+    fn predecessors(&self, coord: &Coord) -> impl Iterator<Item = (Coord, u32)> {
+        let risk = self.map.get(coord) as u32;
+        self.neighborhood
+            .neighbors(self.map.max, *coord)
+            .map(move |neighbor| (neighbor, risk))
+    }
+}
 
-pub struct RiskMap {
-    map: Vec<Vec<u8>>,
+/// Finds the lowest-risk route from the top left to the bottom right, moving between cells
+/// that are neighbors under |neighborhood|, or |None| if the bottom right can't be reached.
+pub fn a_star_in(map: &RiskMap, neighborhood: &impl Neighborhood) -> Option<Route> {
+    a_star_between(map, Coord::ORIGIN, &[map.max], neighborhood)
+}
+
+/// Finds the lowest-risk route from |start| to whichever of |goals| is cheapest to reach,
+/// moving between cells that are neighbors under |neighborhood|; |Moore| allows diagonal
+/// moves. Returns |None| if |start| is off the map or none of |goals| can be reached. Every
+/// cell has a risk of at least 1, so |Neighborhood::min_steps| to the nearest goal never
+/// overestimates the remaining risk.
+pub fn a_star_between(
+    map: &RiskMap,
+    start: Coord,
+    goals: &[Coord],
+    neighborhood: &impl Neighborhood,
+) -> Option<Route> {
+    route_between(map, start, goals, neighborhood, Algorithm::AStar)
+}
+
+/// The same search as |a_star_between|, using Dial's bucket queue instead of a binary heap.
+/// Risks are at most 9 and the heuristic is consistent, so the queue stays small.
+pub fn dial_between(
+    map: &RiskMap,
+    start: Coord,
+    goals: &[Coord],
+    neighborhood: &impl Neighborhood,
+) -> Option<Route> {
+    route_between(map, start, goals, neighborhood, Algorithm::Dial)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    AStar,
+    Dial,
+}
+
+fn route_between(
+    map: &RiskMap,
+    start: Coord,
+    goals: &[Coord],
+    neighborhood: &impl Neighborhood,
+    algorithm: Algorithm,
+) -> Option<Route> {
+    if !map.contains(start) {
+        return None;
+    }
+
+    let graph = RiskGraph { map, neighborhood };
+    let is_goal = |coord: &Coord| goals.contains(coord);
+    let heuristic = |coord: &Coord| {
+        goals
+            .iter()
+            .map(|goal| neighborhood.min_steps(*coord, *goal))
+            .min()
+            .unwrap_or(0)
+    };
+    let visits = TileMap::new(map);
+    let result = match algorithm {
+        Algorithm::AStar => search::a_star_with(&graph, start, is_goal, heuristic, visits),
+        Algorithm::Dial => search::dial_with(&graph, start, is_goal, heuristic, visits),
+    };
+    Some(Route {
+        risk: result.goal_cost()?,
+        path: result.goal_path()?,
+    })
+}
+
+/// The fewest steps to |goal|, which never overestimates the risk of getting there.
+struct MinSteps<N> {
+    neighborhood: N,
     goal: Coord,
-    rows: usize,
-    cols: usize,
 }
 
-impl RiskMap {
-    pub fn new(map: Vec<Vec<u8>>, tiles: usize) -> Self {
-        assert!(tiles > 0);
-        assert!(map.len() > 0);
-        assert!(map[0].len() > 0);
-
-        let rows = map.len();
-        let cols = map[0].len();
-        let goal = Coord {
-            row: (rows * tiles) - 1,
-            col: (cols * tiles) - 1,
+impl<N: Neighborhood> Heuristic<Coord, u32> for MinSteps<N> {
+    fn estimate(&self, coord: &Coord) -> u32 {
+        self.neighborhood.min_steps(*coord, self.goal)
+    }
+}
+
+/// Keeps the lowest-risk route between two cells up to date as risks on a |RiskMap| change,
+/// repairing the previous search rather than starting over. Every call must be given the
+/// same map, and |neighborhood| must be symmetric, so that the cells a cell can be entered
+/// from are its neighbors.
+pub struct Replanner<N> {
+    start: Coord,
+    goal: Coord,
+    neighborhood: N,
+    search: LpaStar<Coord, u32, MinSteps<N>>,
+}
+
+impl<N: Neighborhood + Clone> Replanner<N> {
+    pub fn new(start: Coord, goal: Coord, neighborhood: N) -> Self {
+        let heuristic = MinSteps {
+            neighborhood: neighborhood.clone(),
+            goal,
         };
-        Self {
-            map,
+        Replanner {
+            start,
             goal,
-            rows,
-            cols,
+            neighborhood,
+            search: LpaStar::new(start, goal, heuristic),
         }
     }
 
-    pub fn get(&self, coord: &Coord) -> u8 {
-        let real_row = coord.row % self.rows;
-        let real_col = coord.col % self.cols;
-        let shift = (coord.row / self.rows) + (coord.col / self.cols);
-        let shifted_real_value = self.map[real_row][real_col] + shift as u8;
-        if shifted_real_value > 9 {
-            (shifted_real_value % 10) + 1
-        } else {
-            shifted_real_value
+    /// The lowest-risk route with the risks as they are now, or |None| if there is none.
+    pub fn route(&mut self, map: &RiskMap) -> Option<Route> {
+        if !map.contains(self.start) || !map.contains(self.goal) {
+            return None;
         }
+
+        let graph = RiskGraph {
+            map,
+            neighborhood: &self.neighborhood,
+        };
+        let risk = self.search.compute(&graph)?;
+        let path = self.search.path(&graph)?;
+        Some(Route { risk, path })
+    }
+
+    /// Sets the risk of a cell on |map|, and marks the routes through it for repair.
+    pub fn set_risk(&mut self, map: &mut RiskMap, coord: Coord, risk: u8) {
+        map.set(coord, risk);
+        let graph = RiskGraph {
+            map,
+            neighborhood: &self.neighborhood,
+        };
+        self.search.cost_changed(&graph, coord);
+    }
+
+    /// The number of cells expanded so far, over every route.
+    pub fn expanded(&self) -> usize {
+        self.search.expanded()
     }
 }
 
-pub fn a_star(map: &RiskMap) -> u32 {
-    let mut open_set = BinaryHeap::from([OpenSetValue {
-        coord: ORIGIN,
-        estimated_cost: ORIGIN.distance_to(&map.goal),
-    }]);
+/// Search state for a |RiskMap|, kept in one dense block per tile of the map. A block is only
+/// allocated once the search reaches its tile, so memory grows with the area explored rather
+/// than with the area of the whole tiled map.
+struct TileMap<V> {
+    rows: usize,
+    cols: usize,
+    tiles_across: usize,
+    tiles: Vec<Option<Box<[Option<V>]>>>,
+    len: usize,
+}
 
-    // There is *probably* a way to keep track of the best weight for each position without
-    // making the matrix 5x larger in both dimensions. However, that's above my pay grade.
-    let mut best_weights = vec![vec![u32::MAX; map.goal.col + 1]; map.goal.row + 1];
-    best_weights[ORIGIN.row][ORIGIN.col] = 0;
+impl<V> TileMap<V> {
+    fn new(map: &RiskMap) -> Self {
+        let (max_row, max_col) = map.max.to_indices().expect("the map is not empty");
+        let tiles_down = (max_row + 1) / map.rows;
+        let tiles_across = (max_col + 1) / map.cols;
+        TileMap {
+            rows: map.rows,
+            cols: map.cols,
+            tiles_across,
+            tiles: (0..tiles_down * tiles_across).map(|_| None).collect(),
+            len: 0,
+        }
+    }
 
-    while let Some(current) = open_set.pop() {
-        if current.coord == map.goal {
-            return best_weights[current.coord.row][current.coord.col];
+    /// The tile |coord| is on, and its index within that tile.
+    fn locate(&self, coord: &Coord) -> Option<(usize, usize)> {
+        let (row, col) = coord.to_indices()?;
+        let (tile_row, tile_col) = (row / self.rows, col / self.cols);
+        let tile = tile_row * self.tiles_across + tile_col;
+        if tile_col >= self.tiles_across || tile >= self.tiles.len() {
+            return None;
         }
+        Some((tile, (row % self.rows) * self.cols + col % self.cols))
+    }
+}
 
-        let current_weight = best_weights[current.coord.row][current.coord.col];
-        for neighbor in Coords::new_neighbors(map.goal, current.coord) {
-            let new_neighbor_weight = current_weight + map.get(&neighbor) as u32;
-            let current_neighbor_weight = best_weights[neighbor.row][neighbor.col];
-            if new_neighbor_weight < current_neighbor_weight {
-                best_weights[neighbor.row][neighbor.col] = new_neighbor_weight;
-
-                open_set.push(OpenSetValue {
-                    coord: neighbor,
-                    estimated_cost: new_neighbor_weight as f64 + neighbor.distance_to(&map.goal),
-                });
-            }
+impl<V> NodeMap<Coord, V> for TileMap<V> {
+    fn get(&self, coord: &Coord) -> Option<&V> {
+        let (tile, index) = self.locate(coord)?;
+        self.tiles[tile].as_ref()?[index].as_ref()
+    }
+
+    fn get_mut(&mut self, coord: &Coord) -> Option<&mut V> {
+        let (tile, index) = self.locate(coord)?;
+        self.tiles[tile].as_mut()?[index].as_mut()
+    }
+
+    fn insert(&mut self, coord: Coord, value: V) {
+        let (tile, index) = self.locate(&coord).expect("coordinate is on the map");
+        let cells = self.rows * self.cols;
+        let tile = self.tiles[tile].get_or_insert_with(|| (0..cells).map(|_| None).collect());
+        if tile[index].replace(value).is_none() {
+            self.len += 1;
         }
     }
-    0
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = Coord> + '_ {
+        let tiles = self
+            .tiles
+            .iter()
+            .enumerate()
+            .filter_map(|(tile, cells)| Some((tile, cells.as_ref()?)));
+        tiles.flat_map(move |(tile, cells)| {
+            let top = (tile / self.tiles_across) * self.rows;
+            let left = (tile % self.tiles_across) * self.cols;
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.is_some())
+                .map(move |(index, _)| {
+                    Coord::from_indices(top + index / self.cols, left + index % self.cols)
+                })
+        })
+    }
+}
+
+/// Finds up to |k| of the lowest-risk routes from |start| to |goal| that never enter a cell
+/// twice, lowest first, moving between cells that are neighbors under |neighborhood|.
+pub fn k_lowest_risk_routes(
+    map: &RiskMap,
+    start: Coord,
+    goal: Coord,
+    k: usize,
+    neighborhood: &impl Neighborhood,
+) -> Vec<Route> {
+    if !map.contains(start) || !map.contains(goal) {
+        return Vec::new();
+    }
+
+    let graph = RiskGraph { map, neighborhood };
+    let heuristic = |coord: &Coord| neighborhood.min_steps(*coord, goal);
+    search::k_shortest_paths(&graph, start, goal, k, heuristic)
+        .into_iter()
+        .map(Route::from)
+        .collect()
+}
+
+/// Finds the lowest-risk route from |start| to |goal| by searching from both ends at once,
+/// which explores less of the map than a single search when the two are far apart.
+/// |neighborhood| must be symmetric, so that the cells a cell can be entered from are its
+/// neighbors.
+pub fn bidirectional_between(
+    map: &RiskMap,
+    start: Coord,
+    goal: Coord,
+    neighborhood: &impl Neighborhood,
+) -> Option<Route> {
+    if !map.contains(start) || !map.contains(goal) {
+        return None;
+    }
+
+    let graph = RiskGraph { map, neighborhood };
+    search::bidirectional_dijkstra(&graph, start, goal).map(Route::from)
+}
+
+/// Draws the map with the risk of each cell on |route| and a '.' everywhere else.
+pub fn render_route(map: &RiskMap, route: &Route) -> String {
+    let (rows, cols) = map.max.to_indices().expect("the map is not empty");
+    let mut cells = Grid::new(cols + 1, rows + 1, '.');
+    for coord in &route.path {
+        cells[*coord] = char::from(b'0' + map.get(coord));
+    }
+    cells.to_string()
+}
+
+fn total_risk(map: &RiskMap, neighborhood: &impl Neighborhood) -> Result<u32, AocError> {
+    a_star_in(map, neighborhood)
+        .map(|route| route.risk)
+        .ok_or_else(|| AocError::InvalidInput("the bottom right can't be reached".to_owned()))
+}
+
+pub struct Day15 {
+    map: Grid<u8>,
+}
+
+impl Solution for Day15 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day15 {
+            map: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<u32, AocError> {
+        total_risk(&RiskMap::new(self.map.clone(), 1), &VonNeumann)
+    }
+
+    fn part2(&self) -> Result<u32, AocError> {
+        total_risk(&RiskMap::new(self.map.clone(), 5), &VonNeumann)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc_common::coord::{Metric, Moore, Radius, Toroidal};
+
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day15";
-    const EXAMPLE_1: &'static str = "\
+    const EXAMPLE_1: &str = "\
 1163751742
 1381373672
 2136511328
@@ -123,40 +448,370 @@ mod tests {
 1293138521
 2311944581";
 
-    fn parse_input(input: &str) -> Vec<Vec<u8>> {
-        input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| c.to_digit(10).unwrap() as u8)
-                    .collect()
-            })
-            .collect()
+    #[test]
+    fn problem1_example() {
+        let map = parse_input(EXAMPLE_1).unwrap();
+        assert_eq!(
+            a_star(&RiskMap::new(map, 1)).map(|route| route.risk),
+            Some(40)
+        );
     }
 
     #[test]
-    fn problem1_example() {
-        let map = parse_input(EXAMPLE_1);
-        assert_eq!(a_star(&RiskMap::new(map, 1)), 40);
+    fn problem2_example() {
+        let map = parse_input(EXAMPLE_1).unwrap();
+        assert_eq!(
+            a_star(&RiskMap::new(map, 5)).map(|route| route.risk),
+            Some(315)
+        );
     }
 
     #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let map = parse_input(&content);
-        assert_eq!(a_star(&RiskMap::new(map, 1)), 429);
+    fn dial_examples() {
+        let map = parse_input(EXAMPLE_1).unwrap();
+        assert_eq!(
+            dial(&RiskMap::new(map.clone(), 1)).map(|route| route.risk),
+            Some(40)
+        );
+        assert_eq!(
+            dial(&RiskMap::new(map, 5)).map(|route| route.risk),
+            Some(315)
+        );
     }
 
     #[test]
-    fn problem2_example() {
-        let map = parse_input(EXAMPLE_1);
-        assert_eq!(a_star(&RiskMap::new(map, 5)), 315);
+    fn dial_matches_a_star() {
+        let map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 2);
+        let goals = [Coord::new(19, 19), Coord::new(7, 13)];
+        for start in Grid::new(20, 20, ()).coords() {
+            for neighborhood in [
+                &Radius::new(1, Metric::Manhattan),
+                &Radius::new(1, Metric::Chebyshev),
+                &Radius::new(2, Metric::Manhattan),
+            ] {
+                let a_star = a_star_between(&map, start, &goals, neighborhood);
+                let dial = dial_between(&map, start, &goals, neighborhood);
+                assert_eq!(
+                    a_star.map(|route| route.risk),
+                    dial.map(|route| route.risk),
+                    "from {:?}",
+                    start
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tiled_risks() {
+        let map = RiskMap::new(parse_input("8").unwrap(), 12);
+        assert_eq!(
+            (0..12)
+                .map(|col| map.get(&Coord::new(0, col)))
+                .collect::<Vec<_>>(),
+            [8, 9, 1, 2, 3, 4, 5, 6, 7, 8, 9, 1]
+        );
+        assert_eq!(map.get(&Coord::new(11, 11)), 3);
+    }
+
+    fn allocated_tiles<V>(visits: &TileMap<V>) -> usize {
+        visits.tiles.iter().filter(|tile| tile.is_some()).count()
+    }
+
+    #[test]
+    fn tile_map() {
+        let map = RiskMap::new(parse_input("12\n34").unwrap(), 3);
+        let mut visits = TileMap::new(&map);
+        assert_eq!(visits.locate(&Coord::new(3, 4)), Some((5, 2)));
+        assert_eq!(visits.locate(&Coord::new(0, 6)), None);
+        assert_eq!(visits.locate(&Coord::new(6, 0)), None);
+
+        visits.insert(Coord::new(3, 4), 'a');
+        visits.insert(Coord::new(0, 1), 'b');
+        visits.insert(Coord::new(3, 4), 'c');
+        *visits.get_mut(&Coord::new(0, 1)).unwrap() = 'd';
+        assert_eq!(visits.get(&Coord::new(3, 4)), Some(&'c'));
+        assert_eq!(visits.get(&Coord::new(0, 1)), Some(&'d'));
+        assert_eq!(visits.get(&Coord::new(2, 4)), None);
+        assert_eq!(visits.len(), 2);
+        assert_eq!(allocated_tiles(&visits), 2);
+        assert_eq!(
+            visits.nodes().collect::<Vec<_>>(),
+            [Coord::new(0, 1), Coord::new(3, 4)]
+        );
+    }
+
+    #[test]
+    fn memory_follows_explored_area() {
+        // a hundred by a hundred tiles, a million cells in all
+        let map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 100);
+        let graph = RiskGraph {
+            map: &map,
+            neighborhood: &VonNeumann,
+        };
+        let goal = Coord::new(12, 12);
+        let is_goal = |coord: &Coord| *coord == goal;
+        let heuristic = |coord: &Coord| coord.manhattan_distance(&goal);
+        let tiled = search::a_star_with(
+            &graph,
+            Coord::ORIGIN,
+            is_goal,
+            heuristic,
+            TileMap::new(&map),
+        );
+        let hashed = search::a_star(&graph, Coord::ORIGIN, is_goal, heuristic);
+        assert_eq!(tiled.goal_cost(), hashed.goal_cost());
+        assert_eq!(tiled.reached_count(), hashed.reached_count());
+        assert_eq!(tiled.reached().count(), tiled.reached_count());
+        assert!(allocated_tiles(tiled.visits()) <= 4);
+    }
+
+    #[test]
+    fn set_risk() {
+        let mut map = RiskMap::new(parse_input("12\n34").unwrap(), 2);
+        map.set(Coord::new(1, 3), 9);
+        assert_eq!(map.get(&Coord::new(1, 3)), 9);
+        assert_eq!(map.get(&Coord::new(1, 1)), 4);
+        assert_eq!(map.get(&Coord::new(3, 3)), 6);
+    }
+
+    /// Edits random cells of the map and checks the replanned route against a fresh search
+    /// after every edit.
+    fn replan_randomly(neighborhood: impl Neighborhood + Copy) {
+        let mut map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 2);
+        let (start, goal) = (Coord::new(2, 1), Coord::new(19, 17));
+        let mut replanner = Replanner::new(start, goal, neighborhood);
+        let mut state: u32 = 15;
+        let mut random = |bound: u32| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) % bound
+        };
+
+        for _ in 0..200 {
+            let coord = Coord::new(random(20) as i32, random(20) as i32);
+            replanner.set_risk(&mut map, coord, random(9) as u8 + 1);
+
+            let route = replanner.route(&map).unwrap();
+            let expected = a_star_between(&map, start, &[goal], &neighborhood).unwrap();
+            assert_eq!(route.risk, expected.risk);
+            assert_valid(&map, &route, start, goal);
+        }
+    }
+
+    #[test]
+    fn replanning() {
+        replan_randomly(VonNeumann);
+        replan_randomly(Moore);
+    }
+
+    #[test]
+    fn replanning_reuses_the_search() {
+        let mut map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 5);
+        let mut replanner = Replanner::new(Coord::ORIGIN, Coord::new(49, 49), VonNeumann);
+        assert_eq!(replanner.route(&map).map(|route| route.risk), Some(315));
+        let initial = replanner.expanded();
+
+        let route = replanner.route(&map).unwrap();
+        let cell = route.path[route.path.len() / 2];
+        replanner.set_risk(&mut map, cell, 9);
+        let route = replanner.route(&map).unwrap();
+        assert_eq!(Some(route.risk), a_star(&map).map(|route| route.risk));
+        assert!(replanner.expanded() - initial < initial / 2);
+
+        assert_eq!(
+            Replanner::new(Coord::new(-1, 0), Coord::ORIGIN, VonNeumann).route(&map),
+            None
+        );
+    }
+
+    /// Checks that |route| goes from |start| to |goal| between neighbors, and that its risk is
+    /// the risk of the cells it enters.
+    fn assert_valid(map: &RiskMap, route: &Route, start: Coord, goal: Coord) {
+        assert_eq!(route.path.first(), Some(&start));
+        assert_eq!(route.path.last(), Some(&goal));
+        assert!(route
+            .path
+            .windows(2)
+            .all(|step| step[0].chebyshev_distance(&step[1]) == 1));
+        assert_eq!(
+            route.path[1..]
+                .iter()
+                .map(|coord| map.get(coord) as u32)
+                .sum::<u32>(),
+            route.risk
+        );
+    }
+
+    #[test]
+    fn single_best_routes_match_a_star() {
+        let map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 2);
+        let goal = Coord::new(13, 18);
+        for start in Grid::new(20, 20, ()).coords().step_by(3) {
+            let expected = a_star_between(&map, start, &[goal], &VonNeumann).map(|r| r.risk);
+
+            let routes = k_lowest_risk_routes(&map, start, goal, 1, &VonNeumann);
+            assert_eq!(routes.len(), 1);
+            assert_valid(&map, &routes[0], start, goal);
+            assert_eq!(Some(routes[0].risk), expected, "from {:?}", start);
+
+            let route = bidirectional_between(&map, start, goal, &VonNeumann).unwrap();
+            assert_valid(&map, &route, start, goal);
+            assert_eq!(Some(route.risk), expected, "from {:?}", start);
+
+            let diagonal = bidirectional_between(&map, start, goal, &Moore).map(|r| r.risk);
+            assert_eq!(
+                diagonal,
+                a_star_between(&map, start, &[goal], &Moore).map(|r| r.risk)
+            );
+        }
+
+        assert!(k_lowest_risk_routes(&map, Coord::new(20, 0), goal, 1, &VonNeumann).is_empty());
+        assert_eq!(
+            bidirectional_between(&map, Coord::ORIGIN, Coord::new(0, 20), &VonNeumann),
+            None
+        );
+    }
+
+    /// The risks of every route from |from| to |to| that never enters a cell twice.
+    fn all_route_risks(
+        map: &RiskMap,
+        from: Coord,
+        to: Coord,
+        visited: &mut Vec<Coord>,
+    ) -> Vec<u32> {
+        if from == to {
+            return vec![0];
+        }
+        let mut risks = Vec::new();
+        visited.push(from);
+        for next in VonNeumann.neighbors(map.max_coord(), from) {
+            if !visited.contains(&next) {
+                let risk = map.get(&next) as u32;
+                for rest in all_route_risks(map, next, to, visited) {
+                    risks.push(risk + rest);
+                }
+            }
+        }
+        visited.pop();
+        risks
+    }
+
+    #[test]
+    fn k_lowest_risk() {
+        let map = RiskMap::new(parse_input("131\n529\n174").unwrap(), 1);
+        let (start, goal) = (Coord::ORIGIN, Coord::new(2, 2));
+        let mut expected = all_route_risks(&map, start, goal, &mut Vec::new());
+        expected.sort();
+        assert_eq!(expected.len(), 12);
+
+        let routes = k_lowest_risk_routes(&map, start, goal, 20, &VonNeumann);
+        assert_eq!(
+            routes.iter().map(|route| route.risk).collect::<Vec<_>>(),
+            expected
+        );
+        for (i, route) in routes.iter().enumerate() {
+            assert_valid(&map, route, start, goal);
+            assert!(routes[..i].iter().all(|other| other.path != route.path));
+        }
+        assert_eq!(
+            k_lowest_risk_routes(&map, start, goal, 4, &VonNeumann),
+            routes[..4]
+        );
+    }
+
+    #[test]
+    fn route() {
+        let map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 1);
+        let route = a_star(&map).unwrap();
+        assert_valid(&map, &route, Coord::ORIGIN, Coord::new(9, 9));
+        assert!(route
+            .path
+            .windows(2)
+            .all(|step| step[0].manhattan_distance(&step[1]) == 1));
+        assert_eq!(render_route(&map, &route).lines().count(), 10);
+        assert_eq!(
+            render_route(&map, &route)
+                .matches(|c: char| c != '.' && c != '\n')
+                .count(),
+            route.path.len()
+        );
+    }
+
+    fn risk_between(
+        map: &RiskMap,
+        start: Coord,
+        goals: &[Coord],
+        neighborhood: &impl Neighborhood,
+    ) -> Option<u32> {
+        a_star_between(map, start, goals, neighborhood).map(|route| route.risk)
+    }
+
+    #[test]
+    fn between() {
+        let map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 1);
+        let corners = [Coord::ORIGIN, Coord::new(9, 9)];
+        assert_eq!(
+            risk_between(&map, corners[1], &corners[..1], &VonNeumann),
+            Some(40)
+        );
+        assert_eq!(
+            risk_between(&map, Coord::new(4, 4), &[Coord::new(4, 4)], &VonNeumann),
+            Some(0)
+        );
+        assert_eq!(
+            risk_between(&map, Coord::ORIGIN, &[Coord::new(1, 1)], &VonNeumann),
+            Some(4)
+        );
+        assert_eq!(
+            risk_between(&map, Coord::ORIGIN, &[Coord::new(1, 1)], &Moore),
+            Some(3)
+        );
+
+        // the nearest goal wins
+        assert_eq!(
+            risk_between(
+                &map,
+                Coord::ORIGIN,
+                &[Coord::new(9, 9), Coord::new(0, 2)],
+                &VonNeumann
+            ),
+            Some(7)
+        );
+        assert_eq!(
+            risk_between(&map, Coord::new(5, 5), &corners, &VonNeumann),
+            Some(16)
+        );
+        assert_eq!(
+            risk_between(&map, Coord::new(5, 5), &corners, &Moore),
+            Some(10)
+        );
+
+        assert_eq!(risk_between(&map, Coord::ORIGIN, &[], &VonNeumann), None);
+        assert_eq!(
+            risk_between(&map, Coord::new(-1, 0), &corners, &VonNeumann),
+            None
+        );
+        assert_eq!(
+            risk_between(&map, Coord::ORIGIN, &[Coord::new(10, 0)], &VonNeumann),
+            None
+        );
+    }
+
+    #[test]
+    fn unreachable() {
+        let map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 1);
+        assert_eq!(a_star_in(&map, &Radius::new(0, Metric::Manhattan)), None);
+        assert!(matches!(
+            total_risk(&map, &Radius::new(0, Metric::Manhattan)),
+            Err(AocError::InvalidInput(_))
+        ));
     }
 
     #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let map = parse_input(&content);
-        assert_eq!(a_star(&RiskMap::new(map, 5)), 2844);
+    fn neighborhoods() {
+        let map = RiskMap::new(parse_input(EXAMPLE_1).unwrap(), 1);
+        assert_eq!(total_risk(&map, &VonNeumann), Ok(40));
+        assert_eq!(total_risk(&map, &Radius::new(1, Metric::Manhattan)), Ok(40));
+        assert_eq!(total_risk(&map, &Moore), Ok(20));
+        assert_eq!(total_risk(&map, &Toroidal(VonNeumann)), Ok(3));
     }
 }
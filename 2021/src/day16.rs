@@ -1,5 +1,15 @@
-#[derive(Clone)]
-enum PacketNode {
+use std::fmt::Display;
+
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, Line, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
+
+use crate::error::PuzzleError;
+
+/// What a packet holds: a literal value, or an operator and the subpackets it applies to.
+/// Comparisons always have exactly two subpackets, and minimum and maximum at least one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PacketNode {
     Sum(Vec<Packet>),
     Product(Vec<Packet>),
     Minimum(Vec<Packet>),
@@ -10,103 +20,306 @@ enum PacketNode {
     Equals(Box<Packet>, Box<Packet>),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Packet {
-    node: PacketNode,
-    version: u64,
+    pub node: PacketNode,
+    pub version: u64,
 }
 
-pub struct ParserState<'a> {
-    current: u8,
-    current_bits: u8,
-    rest: &'a str,
-    bit_position: usize,
+impl Packet {
+    /// The type ID the packet is encoded with.
+    pub fn type_id(&self) -> u8 {
+        match self.node {
+            PacketNode::Sum(_) => 0,
+            PacketNode::Product(_) => 1,
+            PacketNode::Minimum(_) => 2,
+            PacketNode::Maximum(_) => 3,
+            PacketNode::Literal(_) => 4,
+            PacketNode::GreaterThan(_, _) => 5,
+            PacketNode::LessThan(_, _) => 6,
+            PacketNode::Equals(_, _) => 7,
+        }
+    }
+
+    pub fn literal(&self) -> Option<u64> {
+        match self.node {
+            PacketNode::Literal(literal) => Some(literal),
+            _ => None,
+        }
+    }
+
+    /// The packet's subpackets in order, which is none for a literal.
+    pub fn subpackets(&self) -> impl Iterator<Item = &Packet> {
+        let (list, pair): (&[Packet], Option<[&Packet; 2]>) = match &self.node {
+            PacketNode::Sum(subpackets)
+            | PacketNode::Product(subpackets)
+            | PacketNode::Minimum(subpackets)
+            | PacketNode::Maximum(subpackets) => (subpackets, None),
+            PacketNode::GreaterThan(left, right)
+            | PacketNode::LessThan(left, right)
+            | PacketNode::Equals(left, right) => (&[], Some([left, right])),
+            PacketNode::Literal(_) => (&[], None),
+        };
+        list.iter().chain(pair.into_iter().flatten())
+    }
+
+    /// Folds the tree from the leaves up, combining each packet with the results for its
+    /// subpackets.
+    pub fn fold<F: PacketFolder>(&self, folder: &mut F) -> F::Output {
+        let subpackets = self
+            .subpackets()
+            .map(|subpacket| subpacket.fold(folder))
+            .collect();
+        folder.fold_packet(self, subpackets)
+    }
 }
 
-impl ParserState<'_> {
-    fn take_bits(&mut self, requested_bits: u8) -> u64 {
-        assert!(self.current < 0x10);
-        assert!(self.current_bits <= 4 && self.current_bits > 0);
-        assert!(requested_bits > 0);
-
-        if requested_bits < self.current_bits {
-            let all_mask = 0xF;
-            let unrequested_count = self.current_bits - requested_bits;
-            let unrequested_mask = (1u8 << unrequested_count) - 1;
-            let mask = all_mask ^ unrequested_mask;
-            let bits = self.current & mask;
-
-            self.current_bits -= requested_bits;
-            self.current &= unrequested_mask;
-            self.bit_position += requested_bits as usize;
-
-            bits as u64 >> unrequested_count
-        } else if requested_bits == self.current_bits {
-            let bits = self.current;
-
-            // We are taking a char to clear out |current|, but we don't
-            // actually need to make sure that it worked -- if |requested_bits|
-            // is 4 and we have the last four of the string in |current|, then
-            // we are at the end of the string and can't take any more characters.
-            // Thats fine, as long as |take_bits| is never called again.
-            self.take_char().unwrap_or_default();
-            self.bit_position += requested_bits as usize;
-
-            bits.into()
-        } else {
-            let mut remaining_bits = requested_bits - self.current_bits;
-            let mut bits = (self.current as u64) << remaining_bits as u64;
-            self.bit_position += self.current_bits as usize;
+/// A computation over a packet tree, for |Packet::fold|.
+pub trait PacketFolder {
+    type Output;
+
+    /// Combines |packet| with the results for each of its subpackets, in order.
+    fn fold_packet(&mut self, packet: &Packet, subpackets: Vec<Self::Output>) -> Self::Output;
+}
 
-            self.take_char().unwrap();
+// How tightly each kind of expression binds. An operand needs parentheses unless it binds
+// tighter than its operator.
+const COMPARISON: u8 = 1;
+const SUM: u8 = 2;
+const PRODUCT: u8 = 3;
+const ATOM: u8 = 4;
 
-            while remaining_bits > 0 {
-                let remaining_bit_chunk = remaining_bits.min(4);
-                remaining_bits -= remaining_bit_chunk;
+/// Writes packets as expressions, along with how tightly each one binds.
+struct Printer;
 
-                let chunk_value = self.take_bits(remaining_bit_chunk);
-                bits += chunk_value << remaining_bits;
-            }
+impl PacketFolder for Printer {
+    type Output = (u8, String);
 
-            bits
+    fn fold_packet(&mut self, packet: &Packet, subpackets: Vec<(u8, String)>) -> (u8, String) {
+        let infix = |operator: &str, precedence: u8| {
+            let operands: Vec<_> = subpackets
+                .iter()
+                .map(|(binds, text)| {
+                    if *binds > precedence {
+                        text.clone()
+                    } else {
+                        format!("({})", text)
+                    }
+                })
+                .collect();
+            (precedence, operands.join(operator))
+        };
+        let call = |name: &str| {
+            let arguments: Vec<_> = subpackets.iter().map(|(_, text)| text.as_str()).collect();
+            (ATOM, format!("{}({})", name, arguments.join(", ")))
+        };
+
+        match &packet.node {
+            PacketNode::Literal(literal) => (ATOM, literal.to_string()),
+            PacketNode::Sum(_) if subpackets.len() >= 2 => infix(" + ", SUM),
+            PacketNode::Sum(_) => call("sum"),
+            PacketNode::Product(_) if subpackets.len() >= 2 => infix(" * ", PRODUCT),
+            PacketNode::Product(_) => call("product"),
+            PacketNode::Minimum(_) => call("min"),
+            PacketNode::Maximum(_) => call("max"),
+            PacketNode::GreaterThan(_, _) => infix(" > ", COMPARISON),
+            PacketNode::LessThan(_, _) => infix(" < ", COMPARISON),
+            PacketNode::Equals(_, _) => infix(" == ", COMPARISON),
         }
     }
+}
+
+/// Writes the packet as an expression, leaving out versions.
+impl Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.fold(&mut Printer).1)
+    }
+}
 
-    pub fn new<'a>(input: &'a str) -> ParserState<'a> {
-        let mut state = ParserState {
-            current: 0,
-            current_bits: 0,
-            rest: input,
-            bit_position: 0,
+/// A field of a BITS packet, as named in errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketField {
+    Version,
+    TypeId,
+    LengthTypeId,
+    SubpacketLength,
+    SubpacketCount,
+    Literal,
+}
+
+impl Display for PacketField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PacketField::Version => "version",
+            PacketField::TypeId => "type ID",
+            PacketField::LengthTypeId => "length type ID",
+            PacketField::SubpacketLength => "subpacket length",
+            PacketField::SubpacketCount => "subpacket count",
+            PacketField::Literal => "literal",
         };
-        state.take_char().unwrap();
-        state
+        write!(f, "{}", name)
     }
+}
+
+/// Why a BITS transmission does not decode to a valid packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketErrorKind {
+    /// The transmission ends partway through |0|.
+    Truncated(PacketField),
+    /// |digit| is not a hex digit, and was to be part of |field|.
+    InvalidHex { digit: char, field: PacketField },
+    /// A literal has more groups than fit in 64 bits.
+    LiteralOverflow,
+    /// An operator's subpackets run past the |bit_length| it gives them.
+    SubpacketOverrun { bit_length: u16 },
+    /// |operator| has |found| subpackets, but requires |expected|.
+    WrongArity {
+        operator: &'static str,
+        expected: &'static str,
+        found: usize,
+    },
+    /// The type ID is not a literal or a known operator.
+    UnknownType(u8),
+}
 
-    fn take_char(&mut self) -> Result<char, ()> {
-        if self.rest.len() == 0 {
-            return Err(());
+impl Display for PacketErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PacketErrorKind::Truncated(field) => write!(f, "truncated {}", field),
+            PacketErrorKind::InvalidHex { digit, field } => {
+                write!(f, "invalid hex digit {:?} in {}", digit, field)
+            }
+            PacketErrorKind::LiteralOverflow => write!(f, "literal does not fit in 64 bits"),
+            PacketErrorKind::SubpacketOverrun { bit_length } => {
+                write!(f, "subpackets overran their length of {} bits", bit_length)
+            }
+            PacketErrorKind::WrongArity {
+                operator,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} requires {} subpackets, found {}",
+                operator, expected, found
+            ),
+            PacketErrorKind::UnknownType(type_id) => write!(f, "unknown type ID {}", type_id),
         }
+    }
+}
 
-        self.current_bits = 4;
-        self.current = u8::from_str_radix(&self.rest[0..1], 16).unwrap();
-        let ret = self.rest.chars().next().unwrap();
+/// The error for a transmission that stops decoding at |bit_position|.
+fn packet_error(bit_position: usize, kind: PacketErrorKind) -> AocError {
+    PuzzleError::MalformedPacket { bit_position, kind }.on_day(16)
+}
 
-        if self.rest.len() > 1 {
-            self.rest = &self.rest[1..];
-        } else {
-            self.rest = "";
+/// A source of the bits of a transmission, which the packet parser reads a field at a time.
+pub trait BitReader {
+    /// Reads the next |requested_bits| bits, which must be at most 32. If there aren't enough
+    /// left, fails with the invalid character that ended the digits, or |None| at the end of the
+    /// transmission. A failed read may still consume some bits.
+    fn read_bits(&mut self, requested_bits: u8) -> Result<u64, Option<char>>;
+
+    /// The number of bits read so far.
+    fn bit_position(&self) -> usize;
+
+    /// The bit position of the first hex digit that hasn't been decoded yet.
+    fn digit_position(&self) -> usize;
+
+    fn error(&self, kind: PacketErrorKind) -> AocError {
+        packet_error(self.bit_position(), kind)
+    }
+
+    /// Reads the next |requested_bits| bits of |field|, which names what is being read in
+    /// errors. A truncated field is reported where it starts, and an invalid hex digit where
+    /// the digit is.
+    fn take_bits(&mut self, requested_bits: u8, field: PacketField) -> Result<u64, AocError> {
+        let start_position = self.bit_position();
+        self.read_bits(requested_bits)
+            .map_err(|invalid| match invalid {
+                Some(c) => packet_error(
+                    self.digit_position(),
+                    PacketErrorKind::InvalidHex { digit: c, field },
+                ),
+                None => packet_error(start_position, PacketErrorKind::Truncated(field)),
+            })
+    }
+}
+
+/// Reads a hex transmission a few bits at a time. Hex digits are decoded into a 64-bit buffer
+/// as they are needed, so most reads are a shift and a mask.
+pub struct ParserState<'a> {
+    /// Bits that have been decoded but not read, with the next one in the top bit.
+    buffer: u64,
+    buffered: u8,
+    /// The hex digits that haven't been decoded into |buffer| yet.
+    rest: &'a str,
+    bit_position: usize,
+}
+
+impl BitReader for ParserState<'_> {
+    /// Reads nothing on failure.
+    fn read_bits(&mut self, requested_bits: u8) -> Result<u64, Option<char>> {
+        assert!(requested_bits > 0 && requested_bits <= 32);
+
+        if self.buffered < requested_bits {
+            self.refill();
+            if self.buffered < requested_bits {
+                return Err(self.rest.chars().next());
+            }
         }
 
-        Ok(ret)
+        let bits = self.buffer >> (64 - requested_bits);
+        self.buffer <<= requested_bits;
+        self.buffered -= requested_bits;
+        self.bit_position += requested_bits as usize;
+        Ok(bits)
+    }
+
+    fn bit_position(&self) -> usize {
+        self.bit_position
+    }
+
+    fn digit_position(&self) -> usize {
+        self.bit_position + self.buffered as usize
     }
 }
 
-fn parse_literal(state: &mut ParserState) -> PacketNode {
+impl ParserState<'_> {
+    /// Decodes hex digits into |buffer| until it is full or a character isn't a hex digit.
+    fn refill(&mut self) {
+        let digits = self.rest.as_bytes();
+        let mut taken = 0;
+        while self.buffered <= 60 {
+            // non-ASCII bytes become non-digit characters, so |taken| stays on a char boundary
+            let Some(digit) = digits.get(taken).and_then(|&b| (b as char).to_digit(16)) else {
+                break;
+            };
+            self.buffer |= (digit as u64) << (60 - self.buffered);
+            self.buffered += 4;
+            taken += 1;
+        }
+        self.rest = &self.rest[taken..];
+    }
+
+    pub fn new<'a>(input: &'a str) -> ParserState<'a> {
+        ParserState {
+            buffer: 0,
+            buffered: 0,
+            rest: input,
+            bit_position: 0,
+        }
+    }
+}
+
+fn parse_literal(state: &mut impl BitReader) -> Result<PacketNode, AocError> {
     const MASK: u64 = 0xF;
     let mut literal = 0u64;
     loop {
-        let segment = state.take_bits(5);
+        if literal.leading_zeros() < 4 {
+            return Err(state.error(PacketErrorKind::LiteralOverflow));
+        }
+
+        let segment = state.take_bits(5, PacketField::Literal)?;
         let segment_value = segment & MASK;
         literal <<= 4;
         literal += segment_value;
@@ -115,245 +328,980 @@ fn parse_literal(state: &mut ParserState) -> PacketNode {
         }
     }
 
-    PacketNode::Literal(literal)
+    Ok(PacketNode::Literal(literal))
 }
 
-fn parse_subpackets_with_bit_length(state: &mut ParserState, bit_length: u16) -> Vec<Packet> {
-    let final_position = state.bit_position + bit_length as usize;
+fn parse_subpackets_with_bit_length(
+    state: &mut impl BitReader,
+    bit_length: u16,
+) -> Result<Vec<Packet>, AocError> {
+    let final_position = state.bit_position() + bit_length as usize;
     let mut subpackets = Vec::new();
-    while state.bit_position < final_position {
-        subpackets.push(parse_packet(state));
+    while state.bit_position() < final_position {
+        subpackets.push(parse_packet(state)?);
     }
 
-    assert_eq!(state.bit_position, final_position);
-    subpackets
+    if state.bit_position() != final_position {
+        return Err(state.error(PacketErrorKind::SubpacketOverrun { bit_length }));
+    }
+    Ok(subpackets)
 }
 
-fn parse_subpackets_with_count(state: &mut ParserState, packet_count: u16) -> Vec<Packet> {
+fn parse_subpackets_with_count(
+    state: &mut impl BitReader,
+    packet_count: u16,
+) -> Result<Vec<Packet>, AocError> {
     let mut subpackets = Vec::new();
     for _ in 0..packet_count {
-        subpackets.push(parse_packet(state));
+        subpackets.push(parse_packet(state)?);
     }
-    subpackets
+    Ok(subpackets)
 }
 
-fn parse_packet(state: &mut ParserState) -> Packet {
-    let version = state.take_bits(3);
-    let type_id = state.take_bits(3);
+/// Parses the next packet from |state|, along with all of its subpackets.
+pub fn parse_packet(state: &mut impl BitReader) -> Result<Packet, AocError> {
+    let start_position = state.bit_position();
+    let version = state.take_bits(3, PacketField::Version)?;
+    let type_id = state.take_bits(3, PacketField::TypeId)?;
 
     let node = match type_id {
-        0b100 => parse_literal(state),
+        0b100 => parse_literal(state)?,
         _ => {
-            let length_type_id = state.take_bits(1);
+            let length_type_id = state.take_bits(1, PacketField::LengthTypeId)?;
             let subpackets = match length_type_id {
                 0 => {
-                    let bit_length = state.take_bits(15) as u16;
-                    parse_subpackets_with_bit_length(state, bit_length)
+                    let bit_length = state.take_bits(15, PacketField::SubpacketLength)? as u16;
+                    parse_subpackets_with_bit_length(state, bit_length)?
                 }
                 _ => {
-                    let packet_count = state.take_bits(11) as u16;
-                    parse_subpackets_with_count(state, packet_count)
+                    let packet_count = state.take_bits(11, PacketField::SubpacketCount)? as u16;
+                    parse_subpackets_with_count(state, packet_count)?
                 }
             };
 
-            match type_id {
-                0 => PacketNode::Sum(subpackets),
-                1 => PacketNode::Product(subpackets),
-                2 => PacketNode::Minimum(subpackets),
-                3 => PacketNode::Maximum(subpackets),
-                5 => {
-                    assert_eq!(subpackets.len(), 2);
-                    PacketNode::GreaterThan(
-                        Box::new(subpackets[0].clone()),
-                        Box::new(subpackets[1].clone()),
-                    )
-                }
-                6 => {
-                    assert_eq!(subpackets.len(), 2);
-                    PacketNode::LessThan(
-                        Box::new(subpackets[0].clone()),
-                        Box::new(subpackets[1].clone()),
-                    )
+            let arity_error = |operator, expected| {
+                packet_error(
+                    start_position,
+                    PacketErrorKind::WrongArity {
+                        operator,
+                        expected,
+                        found: subpackets.len(),
+                    },
+                )
+            };
+            match (type_id, subpackets.len()) {
+                (0, _) => PacketNode::Sum(subpackets),
+                (1, _) => PacketNode::Product(subpackets),
+                (2, 0) => return Err(arity_error("Minimum", "at least 1")),
+                (2, _) => PacketNode::Minimum(subpackets),
+                (3, 0) => return Err(arity_error("Maximum", "at least 1")),
+                (3, _) => PacketNode::Maximum(subpackets),
+                (5..=7, 2) => {
+                    let [left, right]: [Packet; 2] = subpackets.try_into().unwrap();
+                    let (left, right) = (Box::new(left), Box::new(right));
+                    match type_id {
+                        5 => PacketNode::GreaterThan(left, right),
+                        6 => PacketNode::LessThan(left, right),
+                        _ => PacketNode::Equals(left, right),
+                    }
                 }
-                7 => {
-                    assert_eq!(subpackets.len(), 2);
-                    PacketNode::Equals(
-                        Box::new(subpackets[0].clone()),
-                        Box::new(subpackets[1].clone()),
-                    )
+                (5, _) => return Err(arity_error("GreaterThan", "2")),
+                (6, _) => return Err(arity_error("LessThan", "2")),
+                (7, _) => return Err(arity_error("Equals", "2")),
+                _ => {
+                    return Err(packet_error(
+                        start_position + 3,
+                        PacketErrorKind::UnknownType(type_id as u8),
+                    ))
                 }
-                _ => panic!("Invalid type ID {}", type_id),
             }
         }
     };
 
-    Packet { node, version }
+    Ok(Packet { node, version })
+}
+
+/// Parses the hex transmission on the first line of |input| into its outermost packet.
+pub fn parse_input(input: &str) -> Result<Packet, AocError> {
+    let line = non_empty_lines(input)
+        .next()
+        .ok_or_else(|| ParseError::end_of(input))?;
+    let transmission = line.text.trim();
+    if let Some(offset) = transmission.find(|c: char| !c.is_ascii_hexdigit()) {
+        let c = transmission[offset..].chars().next().unwrap();
+        return Err(line
+            .error_at(&transmission[offset..], ParseErrorKind::UnexpectedChar(c))
+            .into());
+    }
+
+    parse_packet(&mut ParserState::new(transmission))
 }
 
-fn sum_versions(packet: &Packet) -> u64 {
-    let mut sum = packet.version;
-    match &packet.node {
-        PacketNode::Sum(subpackets)
-        | PacketNode::Product(subpackets)
-        | PacketNode::Minimum(subpackets)
-        | PacketNode::Maximum(subpackets) => {
-            for subpacket in subpackets.iter() {
-                sum += sum_versions(subpacket);
+/// How an operator packet marks where its subpackets end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthType {
+    /// Length type ID 0: the total length of the subpackets in bits, in 15 bits.
+    Bits,
+    /// Length type ID 1: the number of subpackets, in 11 bits.
+    Count,
+}
+
+/// Bits written most significant first, packed into bytes.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    fn push_bit(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            self.bytes[self.len / 8] |= 0x80 >> (self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    /// Writes the low |count| bits of |value|.
+    fn push(&mut self, value: u64, count: u8) {
+        for bit in (0..count).rev() {
+            self.push_bit((value >> bit) & 1 == 1);
+        }
+    }
+
+    fn append(&mut self, other: &BitWriter) {
+        for bit in 0..other.len {
+            self.push_bit(other.bytes[bit / 8] & (0x80 >> (bit % 8)) != 0);
+        }
+    }
+}
+
+fn write_packet(
+    writer: &mut BitWriter,
+    packet: &Packet,
+    length_type: &mut impl FnMut(&Packet) -> LengthType,
+) -> Result<(), AocError> {
+    let invalid = |reason: String| Err(AocError::InvalidInput(reason));
+    if packet.version > 0b111 {
+        return invalid(format!("version {} does not fit in 3 bits", packet.version));
+    }
+    writer.push(packet.version, 3);
+    writer.push(packet.type_id() as u64, 3);
+
+    if let Some(literal) = packet.literal() {
+        let groups = (64 - literal.leading_zeros()).div_ceil(4).max(1);
+        for group in (0..groups).rev() {
+            writer.push((group > 0) as u64, 1);
+            writer.push(literal >> (group * 4), 4);
+        }
+        return Ok(());
+    }
+
+    let count = packet.subpackets().count();
+    if count == 0 && matches!(packet.node, PacketNode::Minimum(_) | PacketNode::Maximum(_)) {
+        return invalid(format!(
+            "type {} requires at least 1 subpacket",
+            packet.type_id()
+        ));
+    }
+    match length_type(packet) {
+        LengthType::Bits => {
+            let mut subpackets = BitWriter::default();
+            for subpacket in packet.subpackets() {
+                write_packet(&mut subpackets, subpacket, length_type)?;
+            }
+            if subpackets.len >= 1 << 15 {
+                return invalid(format!(
+                    "{} bits of subpackets do not fit in 15 bits",
+                    subpackets.len
+                ));
             }
+            writer.push(0, 1);
+            writer.push(subpackets.len as u64, 15);
+            writer.append(&subpackets);
         }
-        PacketNode::GreaterThan(left, right)
-        | PacketNode::LessThan(left, right)
-        | PacketNode::Equals(left, right) => {
-            sum += sum_versions(left);
-            sum += sum_versions(right);
+        LengthType::Count => {
+            if count >= 1 << 11 {
+                return invalid(format!("{} subpackets do not fit in 11 bits", count));
+            }
+            writer.push(1, 1);
+            writer.push(count as u64, 11);
+            for subpacket in packet.subpackets() {
+                write_packet(writer, subpacket, length_type)?;
+            }
         }
-        _ => (),
     }
-    sum
+    Ok(())
 }
 
-pub fn problem1(state: &mut ParserState) -> u64 {
-    let root = parse_packet(state);
-    sum_versions(&root)
+/// Encodes |packet| as a hex transmission, padded with zeros to a whole number of bytes.
+/// |length_type| chooses how each operator packet marks where its subpackets end.
+pub fn encode(
+    packet: &Packet,
+    mut length_type: impl FnMut(&Packet) -> LengthType,
+) -> Result<String, AocError> {
+    let mut writer = BitWriter::default();
+    write_packet(&mut writer, packet, &mut length_type)?;
+    Ok(writer
+        .bytes
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect())
 }
 
-fn evaluate_packet(packet: &Packet) -> u64 {
-    match &packet.node {
-        PacketNode::Sum(subpackets) => subpackets.iter().map(evaluate_packet).sum(),
-        PacketNode::Product(subpackets) => subpackets.iter().map(evaluate_packet).product(),
-        PacketNode::Minimum(subpackets) => subpackets.iter().map(evaluate_packet).min().unwrap(),
-        PacketNode::Maximum(subpackets) => subpackets.iter().map(evaluate_packet).max().unwrap(),
-        PacketNode::Literal(literal) => *literal,
-        PacketNode::LessThan(left, right) => {
-            if evaluate_packet(left) < evaluate_packet(right) {
-                1
-            } else {
-                0
-            }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token {
+    Number,
+    Name,
+    Plus,
+    Star,
+    Less,
+    Greater,
+    Equals,
+    Open,
+    Close,
+    Comma,
+}
+
+/// Splits |line| into tokens, each with the text it was read from.
+fn tokenize<'a>(line: &Line<'a>) -> Result<Vec<(Token, &'a str)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut rest = line.text.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let (token, length) = match c {
+            '0'..='9' => (
+                Token::Number,
+                rest.find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len()),
+            ),
+            'a'..='z' => (
+                Token::Name,
+                rest.find(|c: char| !c.is_ascii_lowercase())
+                    .unwrap_or(rest.len()),
+            ),
+            '=' if rest.starts_with("==") => (Token::Equals, 2),
+            '+' => (Token::Plus, 1),
+            '*' => (Token::Star, 1),
+            '<' => (Token::Less, 1),
+            '>' => (Token::Greater, 1),
+            '(' => (Token::Open, 1),
+            ')' => (Token::Close, 1),
+            ',' => (Token::Comma, 1),
+            _ => return Err(line.error_at(rest, ParseErrorKind::UnexpectedChar(c))),
+        };
+        tokens.push((token, &rest[..length]));
+        rest = rest[length..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the tokens of one line, with a method per level of
+/// precedence.
+struct Parser<'a> {
+    line: Line<'a>,
+    tokens: Vec<(Token, &'a str)>,
+    next: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.next).map(|(token, _)| *token)
+    }
+
+    /// Takes the next token if it is |token|.
+    fn eat(&mut self, token: Token) -> bool {
+        let matches = self.peek() == Some(token);
+        if matches {
+            self.next += 1;
+        }
+        matches
+    }
+
+    /// An error at the next token, or at the end of the line if there are none left.
+    fn expected(&self, what: &'static str) -> ParseError {
+        match self.tokens.get(self.next) {
+            Some((_, text)) => self.line.error_at(text, ParseErrorKind::Expected(what)),
+            None => self.line.error_at_end(ParseErrorKind::Expected(what)),
+        }
+    }
+
+    fn expect(&mut self, token: Token, what: &'static str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.expected(what))
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Packet, ParseError> {
+        let left = self.sum()?;
+        let node: fn(Box<Packet>, Box<Packet>) -> PacketNode = match self.peek() {
+            Some(Token::Less) => PacketNode::LessThan,
+            Some(Token::Greater) => PacketNode::GreaterThan,
+            Some(Token::Equals) => PacketNode::Equals,
+            _ => return Ok(left),
+        };
+        self.next += 1;
+        let right = self.sum()?;
+        Ok(unversioned(node(Box::new(left), Box::new(right))))
+    }
+
+    fn sum(&mut self) -> Result<Packet, ParseError> {
+        let mut terms = vec![self.product()?];
+        while self.eat(Token::Plus) {
+            terms.push(self.product()?);
         }
-        PacketNode::GreaterThan(left, right) => {
-            if evaluate_packet(left) > evaluate_packet(right) {
-                1
-            } else {
-                0
+        Ok(single_or(terms, PacketNode::Sum))
+    }
+
+    fn product(&mut self) -> Result<Packet, ParseError> {
+        let mut factors = vec![self.atom()?];
+        while self.eat(Token::Star) {
+            factors.push(self.atom()?);
+        }
+        Ok(single_or(factors, PacketNode::Product))
+    }
+
+    fn atom(&mut self) -> Result<Packet, ParseError> {
+        let Some(&(token, text)) = self.tokens.get(self.next) else {
+            return Err(self.expected("a number, a function or `(`"));
+        };
+        match token {
+            Token::Number => {
+                self.next += 1;
+                Ok(unversioned(PacketNode::Literal(
+                    self.line.parse_number(text)?,
+                )))
+            }
+            Token::Open => {
+                self.next += 1;
+                let inner = self.comparison()?;
+                self.expect(Token::Close, "`)`")?;
+                Ok(inner)
+            }
+            Token::Name => {
+                let node: fn(Vec<Packet>) -> PacketNode = match text {
+                    "sum" => PacketNode::Sum,
+                    "product" => PacketNode::Product,
+                    "min" => PacketNode::Minimum,
+                    "max" => PacketNode::Maximum,
+                    _ => return Err(self.expected("`sum`, `product`, `min` or `max`")),
+                };
+                self.next += 1;
+                self.expect(Token::Open, "`(`")?;
+                let arguments = self.arguments()?;
+                if arguments.is_empty() && matches!(text, "min" | "max") {
+                    return Err(self
+                        .line
+                        .error_at(text, ParseErrorKind::Expected("an argument")));
+                }
+                Ok(unversioned(node(arguments)))
             }
+            _ => Err(self.expected("a number, a function or `(`")),
         }
-        PacketNode::Equals(left, right) => {
-            if evaluate_packet(left) == evaluate_packet(right) {
-                1
-            } else {
-                0
+    }
+
+    /// Arguments up to and including the closing parenthesis.
+    fn arguments(&mut self) -> Result<Vec<Packet>, ParseError> {
+        let mut arguments = Vec::new();
+        if self.eat(Token::Close) {
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.comparison()?);
+            if self.eat(Token::Close) {
+                return Ok(arguments);
             }
+            self.expect(Token::Comma, "`,` or `)`")?;
         }
     }
 }
 
-pub fn problem2(state: &mut ParserState) -> u64 {
-    let root = parse_packet(state);
+fn unversioned(node: PacketNode) -> Packet {
+    Packet { node, version: 0 }
+}
+
+/// The only packet in |packets|, or |node| applied to all of them.
+fn single_or(mut packets: Vec<Packet>, node: fn(Vec<Packet>) -> PacketNode) -> Packet {
+    if packets.len() == 1 {
+        packets.pop().unwrap()
+    } else {
+        unversioned(node(packets))
+    }
+}
+
+/// Parses the expression on the only non-blank line of |source| into a packet tree, for
+/// example `max(3, 7 * (2 + 1)) > 5`. Numbers are literals, `+` and `*` are sums and products,
+/// `<`, `>` and `==` are comparisons, and `sum`, `product`, `min` and `max` take any number of
+/// arguments. `*` binds tighter than `+`, which binds tighter than the comparisons, and
+/// comparisons can't be chained without parentheses. Versions aren't part of the syntax, so
+/// every packet has version 0.
+pub fn parse_expression(source: &str) -> Result<Packet, ParseError> {
+    let mut lines = non_empty_lines(source);
+    let line = lines.next().ok_or_else(|| ParseError::end_of(source))?;
+    if let Some(extra) = lines.next() {
+        return Err(extra.error_at(extra.text, ParseErrorKind::Expected("a single line")));
+    }
+
+    let mut parser = Parser {
+        line,
+        tokens: tokenize(&line)?,
+        next: 0,
+    };
+    let packet = parser.comparison()?;
+    if parser.peek().is_some() {
+        return Err(parser.expected("an operator or the end of the expression"));
+    }
+    Ok(packet)
+}
+
+/// Compiles the expression in |source| to a hex transmission, with every operator using
+/// |length_type|.
+pub fn compile(source: &str, length_type: LengthType) -> Result<String, AocError> {
+    encode(&parse_expression(source)?, |_| length_type)
+}
+
+/// Decodes a hex transmission and writes it back out as an expression.
+pub fn decompile(transmission: &str) -> Result<String, AocError> {
+    Ok(parse_input(transmission)?.to_string())
+}
+
+/// Adds up the versions of every packet in the tree.
+pub struct VersionSum;
+
+impl PacketFolder for VersionSum {
+    type Output = u64;
+
+    fn fold_packet(&mut self, packet: &Packet, subpackets: Vec<u64>) -> u64 {
+        packet.version + subpackets.iter().sum::<u64>()
+    }
+}
+
+pub fn sum_versions(packet: &Packet) -> u64 {
+    packet.fold(&mut VersionSum)
+}
+
+pub fn problem1(state: &mut impl BitReader) -> Result<u64, AocError> {
+    let root = parse_packet(state)?;
+    Ok(sum_versions(&root))
+}
+
+/// Computes the value of the expression the tree encodes. Fails if a sum or product doesn't fit
+/// in 64 bits, or if a hand-built tree has a minimum or maximum with no subpackets.
+pub struct Evaluate;
+
+impl PacketFolder for Evaluate {
+    type Output = Result<u64, AocError>;
+
+    fn fold_packet(
+        &mut self,
+        packet: &Packet,
+        subpackets: Vec<Result<u64, AocError>>,
+    ) -> Result<u64, AocError> {
+        let values = subpackets.into_iter().collect::<Result<Vec<_>, _>>()?;
+        let overflow = |name: &str| AocError::InvalidInput(format!("{} overflows 64 bits", name));
+        let empty = |name: &str| AocError::InvalidInput(format!("{} has no subpackets", name));
+        match &packet.node {
+            PacketNode::Sum(_) => values
+                .iter()
+                .try_fold(0u64, |total, value| total.checked_add(*value))
+                .ok_or_else(|| overflow("Sum")),
+            PacketNode::Product(_) => values
+                .iter()
+                .try_fold(1u64, |total, value| total.checked_mul(*value))
+                .ok_or_else(|| overflow("Product")),
+            PacketNode::Minimum(_) => values.iter().copied().min().ok_or_else(|| empty("Minimum")),
+            PacketNode::Maximum(_) => values.iter().copied().max().ok_or_else(|| empty("Maximum")),
+            PacketNode::Literal(literal) => Ok(*literal),
+            PacketNode::GreaterThan(_, _) => Ok((values[0] > values[1]) as u64),
+            PacketNode::LessThan(_, _) => Ok((values[0] < values[1]) as u64),
+            PacketNode::Equals(_, _) => Ok((values[0] == values[1]) as u64),
+        }
+    }
+}
+
+pub fn evaluate_packet(packet: &Packet) -> Result<u64, AocError> {
+    packet.fold(&mut Evaluate)
+}
+
+pub fn problem2(state: &mut impl BitReader) -> Result<u64, AocError> {
+    let root = parse_packet(state)?;
     evaluate_packet(&root)
 }
 
+pub struct Day16 {
+    root: Packet,
+}
+
+impl Solution for Day16 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day16 {
+            root: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<u64, AocError> {
+        Ok(sum_versions(&self.root))
+    }
+
+    fn part2(&self) -> Result<u64, AocError> {
+        evaluate_packet(&self.root)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day16";
+    const EXAMPLES: [&str; 15] = [
+        "D2FE28",
+        "38006F45291200",
+        "EE00D40C823060",
+        "8A004A801A8002F478",
+        "620080001611562C8802118E34",
+        "C0015000016115A2E0802F182340",
+        "A0016C880162017C3686B18A3D4780",
+        "C200B40A82",
+        "04005AC33890",
+        "880086C3E88112",
+        "CE00C43D881120",
+        "D8005AC2A8F0",
+        "F600BC2D8F",
+        "9C005AC2F8F0",
+        "9C0141080250320F1802104A08",
+    ];
 
     #[test]
     fn problem1_example1() {
         let mut state = ParserState::new("38006F45291200");
-        assert_eq!(problem1(&mut state), 9);
+        assert_eq!(problem1(&mut state), Ok(9));
     }
 
     #[test]
     fn problem1_example2() {
         let mut state = ParserState::new("EE00D40C823060");
-        assert_eq!(problem1(&mut state), 14);
+        assert_eq!(problem1(&mut state), Ok(14));
     }
 
     #[test]
     fn problem1_example3() {
         let mut state = ParserState::new("8A004A801A8002F478");
-        assert_eq!(problem1(&mut state), 16);
+        assert_eq!(problem1(&mut state), Ok(16));
     }
 
     #[test]
     fn problem1_example4() {
         let mut state = ParserState::new("C0015000016115A2E0802F182340");
-        assert_eq!(problem1(&mut state), 23);
+        assert_eq!(problem1(&mut state), Ok(23));
     }
 
     #[test]
     fn problem1_example5() {
         let mut state = ParserState::new("620080001611562C8802118E34");
-        assert_eq!(problem1(&mut state), 12);
+        assert_eq!(problem1(&mut state), Ok(12));
     }
 
     #[test]
     fn problem1_example6() {
         let mut state = ParserState::new("A0016C880162017C3686B18A3D4780");
-        assert_eq!(problem1(&mut state), 31);
+        assert_eq!(problem1(&mut state), Ok(31));
+    }
+
+    #[test]
+    fn parse_error() {
+        let err = match parse_input("\n  38006F4G291200\n") {
+            Err(AocError::Parse(err)) => err,
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!((err.line, err.column), (2, 10));
+    }
+
+    #[test]
+    fn malformed_packet() {
+        let malformed = |bit_position, kind| Err(packet_error(bit_position, kind));
+
+        // a literal whose last group is cut off
+        assert_eq!(
+            problem1(&mut ParserState::new("D2FE2")),
+            malformed(16, PacketErrorKind::Truncated(PacketField::Literal))
+        );
+        assert_eq!(
+            problem1(&mut ParserState::new("")),
+            malformed(0, PacketErrorKind::Truncated(PacketField::Version))
+        );
+        // an operator cut off inside its subpacket count
+        assert_eq!(
+            problem1(&mut ParserState::new("EE0")),
+            malformed(7, PacketErrorKind::Truncated(PacketField::SubpacketCount))
+        );
+        assert_eq!(
+            problem1(&mut ParserState::new("D2xE28")),
+            malformed(
+                8,
+                PacketErrorKind::InvalidHex {
+                    digit: 'x',
+                    field: PacketField::Literal
+                }
+            )
+        );
+        // GreaterThan with three literal subpackets
+        assert_eq!(
+            problem2(&mut ParserState::new("D600C40881102")),
+            malformed(
+                0,
+                PacketErrorKind::WrongArity {
+                    operator: "GreaterThan",
+                    expected: "2",
+                    found: 3
+                }
+            )
+        );
+        assert_eq!(
+            parse_input("D2FE2\n").unwrap_err().to_string(),
+            "malformed packet at bit 16: truncated literal"
+        );
     }
 
     #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let mut state = ParserState::new(&content);
-        assert_eq!(problem1(&mut state), 897);
+    fn tree() {
+        // 2 * 3 < 7, with versions 0 to 3
+        let root = parse_input("3A0091001670420D438").unwrap();
+        assert_eq!(root.version, 1);
+        assert_eq!(root.type_id(), 6);
+        assert_eq!(root.literal(), None);
+
+        let subpackets: Vec<_> = root.subpackets().collect();
+        assert_eq!(subpackets.len(), 2);
+        assert_eq!(subpackets[0].type_id(), 1);
+        assert_eq!(
+            subpackets[0]
+                .subpackets()
+                .map(|packet| packet.literal())
+                .collect::<Vec<_>>(),
+            [Some(2), Some(3)]
+        );
+        assert_eq!(subpackets[1].node, PacketNode::Literal(7));
+        assert_eq!(subpackets[1].subpackets().count(), 0);
+    }
+
+    /// Counts the packets at each depth of the tree.
+    struct Depths;
+
+    impl PacketFolder for Depths {
+        type Output = Vec<usize>;
+
+        fn fold_packet(&mut self, _: &Packet, subpackets: Vec<Vec<usize>>) -> Vec<usize> {
+            let mut depths = vec![1];
+            for subpacket in subpackets {
+                for (depth, count) in subpacket.into_iter().enumerate() {
+                    match depths.get_mut(depth + 1) {
+                        Some(total) => *total += count,
+                        None => depths.push(count),
+                    }
+                }
+            }
+            depths
+        }
+    }
+
+    #[test]
+    fn fold() {
+        let root = parse_input("3A0091001670420D438").unwrap();
+        assert_eq!(root.fold(&mut Depths), [1, 2, 2]);
+        assert_eq!(root.fold(&mut VersionSum), 8);
+        assert_eq!(root.fold(&mut Evaluate), Ok(1));
+
+        let root = parse_input("A0016C880162017C3686B18A3D4780").unwrap();
+        assert_eq!(root.fold(&mut Depths), [1, 1, 1, 5]);
+    }
+
+    #[test]
+    fn evaluate_errors() {
+        let evaluate = |node| evaluate_packet(&Packet { node, version: 0 });
+        let invalid = |result| matches!(result, Err(AocError::InvalidInput(_)));
+
+        assert!(invalid(evaluate(PacketNode::Minimum(vec![]))));
+        assert!(invalid(evaluate(PacketNode::Maximum(vec![]))));
+        let big = || vec![literal(0, u64::MAX - 1), literal(0, 2)];
+        assert!(invalid(evaluate(PacketNode::Sum(big()))));
+        assert!(invalid(evaluate(PacketNode::Product(big()))));
+
+        let limit = vec![literal(0, u64::MAX - 1), literal(0, 1)];
+        assert_eq!(evaluate(PacketNode::Sum(limit)), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn part2_overflow() {
+        let sum = Packet {
+            node: PacketNode::Sum(vec![literal(0, u64::MAX), literal(0, 1)]),
+            version: 0,
+        };
+        let day = Day16::parse(&encode(&sum, |_| LengthType::Count).unwrap()).unwrap();
+        assert_eq!(day.part1(), Ok(0));
+        assert!(matches!(day.part2(), Err(AocError::InvalidInput(_))));
+    }
+
+    fn literal(version: u64, literal: u64) -> Packet {
+        Packet {
+            node: PacketNode::Literal(literal),
+            version,
+        }
+    }
+
+    #[test]
+    fn encode_examples() {
+        let decode = |hex: &str| parse_input(hex).unwrap();
+        assert_eq!(
+            encode(&decode("D2FE28"), |_| LengthType::Bits),
+            Ok("D2FE28".to_owned())
+        );
+        assert_eq!(
+            encode(&decode("38006F45291200"), |_| LengthType::Bits),
+            Ok("38006F45291200".to_owned())
+        );
+        assert_eq!(
+            encode(&decode("EE00D40C823060"), |_| LengthType::Count),
+            Ok("EE00D40C823060".to_owned())
+        );
+
+        for example in EXAMPLES {
+            let packet = decode(example);
+            for length_type in [LengthType::Bits, LengthType::Count] {
+                let hex = encode(&packet, |_| length_type).unwrap();
+                assert_eq!(decode(&hex), packet, "{} as {:?}", example, length_type);
+            }
+        }
+    }
+
+    #[test]
+    fn encode_errors() {
+        let invalid = |packet: Packet, length_type: LengthType| {
+            matches!(
+                encode(&packet, |_| length_type),
+                Err(AocError::InvalidInput(_))
+            )
+        };
+        assert!(invalid(literal(8, 1), LengthType::Bits));
+        assert!(invalid(
+            Packet {
+                node: PacketNode::Minimum(Vec::new()),
+                version: 0
+            },
+            LengthType::Count
+        ));
+
+        // 3000 literals of 11 bits each are too many to count, and too long to measure
+        let sum = Packet {
+            node: PacketNode::Sum(vec![literal(0, 1); 3000]),
+            version: 0,
+        };
+        assert!(invalid(sum.clone(), LengthType::Count));
+        assert!(invalid(sum, LengthType::Bits));
+        let sum = Packet {
+            node: PacketNode::Sum(vec![literal(0, 1); 2047]),
+            version: 0,
+        };
+        assert!(!invalid(sum, LengthType::Count));
+    }
+
+    /// Packet trees up to four levels deep, with operators of every type.
+    fn packets() -> impl Strategy<Value = Packet> {
+        let leaf = (0..8u64, any::<u64>()).prop_map(|(version, value)| literal(version, value));
+        leaf.prop_recursive(4, 64, 4, |subpacket| {
+            let list = (
+                0..8u64,
+                0..4u8,
+                prop::collection::vec(subpacket.clone(), 1..5),
+            );
+            let pair = (0..8u64, 0..3u8, subpacket.clone(), subpacket);
+            prop_oneof![
+                list.prop_map(|(version, kind, subpackets)| Packet {
+                    node: match kind {
+                        0 => PacketNode::Sum(subpackets),
+                        1 => PacketNode::Product(subpackets),
+                        2 => PacketNode::Minimum(subpackets),
+                        _ => PacketNode::Maximum(subpackets),
+                    },
+                    version,
+                }),
+                pair.prop_map(|(version, kind, left, right)| {
+                    let (left, right) = (Box::new(left), Box::new(right));
+                    Packet {
+                        node: match kind {
+                            0 => PacketNode::GreaterThan(left, right),
+                            1 => PacketNode::LessThan(left, right),
+                            _ => PacketNode::Equals(left, right),
+                        },
+                        version,
+                    }
+                }),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn encode_round_trip(packet in packets(), choices in any::<u64>()) {
+            // each operator picks its length type from the next bit of |choices|
+            let mut operator = 0;
+            let hex = encode(&packet, |_| {
+                operator += 1;
+                if (choices >> (operator % 64)) & 1 == 0 {
+                    LengthType::Bits
+                } else {
+                    LengthType::Count
+                }
+            })
+            .unwrap();
+            prop_assert_eq!(parse_input(&hex), Ok(packet));
+        }
+    }
+
+    fn evaluate_expression(source: &str) -> u64 {
+        evaluate_packet(&parse_expression(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn expression_evaluation() {
+        assert_eq!(evaluate_expression("max(3, 7 * (2 + 1)) > 5"), 1);
+        assert_eq!(evaluate_expression("1 + 2 * 3"), 7);
+        assert_eq!(evaluate_expression("(1 + 2) * 3"), 9);
+        assert_eq!(evaluate_expression("min(4, 2, 8) == 2"), 1);
+        assert_eq!(evaluate_expression("sum() + product()"), 1);
+        assert_eq!(evaluate_expression(" 2<1 \n"), 0);
+    }
+
+    #[test]
+    fn expression_structure() {
+        let sum = parse_expression("1 + 2 + 3").unwrap();
+        assert_eq!(sum.type_id(), 0);
+        assert_eq!(sum.subpackets().count(), 3);
+        assert_eq!(parse_expression("(((42)))").unwrap().literal(), Some(42));
+    }
+
+    #[test]
+    fn expression_syntax_errors() {
+        let error = |source: &str| {
+            let err = parse_expression(source).unwrap_err();
+            (err.column, err.kind)
+        };
+        assert_eq!(
+            error("1 + * 2"),
+            (5, ParseErrorKind::Expected("a number, a function or `(`"))
+        );
+        assert_eq!(
+            error("max(1, 2"),
+            (9, ParseErrorKind::Expected("`,` or `)`"))
+        );
+        assert_eq!(
+            error("1 < 2 < 3"),
+            (
+                7,
+                ParseErrorKind::Expected("an operator or the end of the expression")
+            )
+        );
+        assert_eq!(error("min()"), (1, ParseErrorKind::Expected("an argument")));
+        assert_eq!(
+            error("avg(1)"),
+            (
+                1,
+                ParseErrorKind::Expected("`sum`, `product`, `min` or `max`")
+            )
+        );
+        assert_eq!(error("1 = 1"), (3, ParseErrorKind::UnexpectedChar('=')));
+        assert!(matches!(
+            error("99999999999999999999").1,
+            ParseErrorKind::InvalidNumber(_)
+        ));
+        assert_eq!(parse_expression("1\n2").unwrap_err().line, 2);
+        assert_eq!(
+            parse_expression("\n").unwrap_err().kind,
+            ParseErrorKind::UnexpectedEnd
+        );
+    }
+
+    #[test]
+    fn compile_and_decompile() {
+        for source in [
+            "max(3, 7 * (2 + 1)) > 5",
+            "1 + 2 * 3",
+            "(1 + 2) * 3",
+            "1 + (2 + 3)",
+            "(1 < 2) == (3 > 4)",
+            "sum(5) * product() * min(1) * max(2, 3)",
+        ] {
+            for length_type in [LengthType::Bits, LengthType::Count] {
+                let hex = compile(source, length_type).unwrap();
+                assert_eq!(decompile(&hex), Ok(source.to_owned()));
+            }
+        }
+
+        for (hex, source) in [
+            ("C200B40A82", "1 + 2"),
+            ("04005AC33890", "6 * 9"),
+            ("880086C3E88112", "min(7, 8, 9)"),
+            ("CE00C43D881120", "max(7, 8, 9)"),
+            ("D8005AC2A8F0", "5 < 15"),
+            ("F600BC2D8F", "5 > 15"),
+            ("9C005AC2F8F0", "5 == 15"),
+            ("9C0141080250320F1802104A08", "1 + 3 == 2 * 2"),
+        ] {
+            assert_eq!(decompile(hex), Ok(source.to_owned()));
+        }
     }
 
     #[test]
     fn problem2_example1() {
         let mut state = ParserState::new("C200B40A82");
-        assert_eq!(problem2(&mut state), 3);
+        assert_eq!(problem2(&mut state), Ok(3));
     }
 
     #[test]
     fn problem2_example2() {
         let mut state = ParserState::new("04005AC33890");
-        assert_eq!(problem2(&mut state), 54);
+        assert_eq!(problem2(&mut state), Ok(54));
     }
 
     #[test]
     fn problem2_example3() {
         let mut state = ParserState::new("880086C3E88112");
-        assert_eq!(problem2(&mut state), 7);
+        assert_eq!(problem2(&mut state), Ok(7));
     }
 
     #[test]
     fn problem2_example4() {
         let mut state = ParserState::new("CE00C43D881120");
-        assert_eq!(problem2(&mut state), 9);
+        assert_eq!(problem2(&mut state), Ok(9));
     }
 
     #[test]
     fn problem2_example5() {
         let mut state = ParserState::new("D8005AC2A8F0");
-        assert_eq!(problem2(&mut state), 1);
+        assert_eq!(problem2(&mut state), Ok(1));
     }
 
     #[test]
     fn problem2_example6() {
         let mut state = ParserState::new("F600BC2D8F");
-        assert_eq!(problem2(&mut state), 0);
+        assert_eq!(problem2(&mut state), Ok(0));
     }
 
     #[test]
     fn problem2_example7() {
         let mut state = ParserState::new("9C005AC2F8F0");
-        assert_eq!(problem2(&mut state), 0);
+        assert_eq!(problem2(&mut state), Ok(0));
     }
 
     #[test]
     fn problem2_example8() {
         let mut state = ParserState::new("9C0141080250320F1802104A08");
-        assert_eq!(problem2(&mut state), 1);
-    }
-
-    #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let mut state = ParserState::new(&content);
-        assert_eq!(problem2(&mut state), 9485076995911);
+        assert_eq!(problem2(&mut state), Ok(1));
     }
 }
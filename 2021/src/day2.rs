@@ -1,9 +1,38 @@
+use std::str::FromStr;
+
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, Line, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
+
+#[derive(Debug)]
 pub enum Direction {
     Forward(i32),
     Down(i32),
     Up(i32),
 }
 
+impl FromStr for Direction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line { number: 1, text: s };
+        let (name, amount) = line.split_once(" ", "a direction and an amount")?;
+        let amount = line.parse_number(amount)?;
+        match name {
+            "forward" => Ok(Direction::Forward(amount)),
+            "down" => Ok(Direction::Down(amount)),
+            "up" => Ok(Direction::Up(amount)),
+            _ => Err(line.error_at(name, ParseErrorKind::Expected("forward, down or up"))),
+        }
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Direction>, ParseError> {
+    non_empty_lines(input)
+        .map(|line| line.parse(line.text.trim()))
+        .collect()
+}
+
 pub fn problem1(directions: &[Direction]) -> i32 {
     let mut horizontal = 0;
     let mut depth = 0;
@@ -34,16 +63,33 @@ pub fn problem2(directions: &[Direction]) -> i32 {
     horizontal * depth
 }
 
+pub struct Day2 {
+    directions: Vec<Direction>,
+}
+
+impl Solution for Day2 {
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day2 {
+            directions: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<i32, AocError> {
+        Ok(problem1(&self.directions))
+    }
+
+    fn part2(&self) -> Result<i32, AocError> {
+        Ok(problem2(&self.directions))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use std::path::Path;
-
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day2";
-
     #[test]
     fn problem1_example() {
         let directions = [
@@ -57,35 +103,12 @@ mod tests {
         assert_eq!(problem1(&directions), 150);
     }
 
-    fn load_data() -> Vec<Direction> {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-        reader
-            .lines()
-            .map(|line| {
-                line.unwrap()
-                    .split(' ')
-                    .map(|split| split.to_string())
-                    .collect::<Vec<String>>()
-            })
-            .filter_map(|strings| match [strings[0].as_str(), strings[1].as_str()] {
-                ["forward", f] => Some(Direction::Forward(f.parse::<i32>().unwrap())),
-                ["down", d] => Some(Direction::Down(d.parse::<i32>().unwrap())),
-                ["up", u] => Some(Direction::Up(u.parse::<i32>().unwrap())),
-                _ => None,
-            })
-            .collect()
-    }
-
     #[test]
-    fn problem1_real() {
-        let input = load_data();
-        assert_eq!(problem1(input.as_slice()), 1636725);
-    }
+    fn parse_error() {
+        let err = parse_input("forward 5\nsideways 3\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
 
-    #[test]
-    fn problem2_real() {
-        let input = load_data();
-        assert_eq!(problem2(input.as_slice()), 1872757425);
+        let err = parse_input("forward 5\ndown x\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 6));
     }
 }
@@ -1,5 +1,11 @@
 use std::cmp::Ordering;
 
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
+
+use crate::error::PuzzleError;
+
 fn count_with_bit_set(valid_set: &[u32], bit: u32) -> usize {
     valid_set.iter().fold(
         0,
@@ -14,10 +20,7 @@ fn count_with_bit_set(valid_set: &[u32], bit: u32) -> usize {
 }
 
 fn ogr_filter(most_common_bit: Option<u32>) -> u32 {
-    match most_common_bit {
-        Some(bit) => bit,
-        None => 1,
-    }
+    most_common_bit.unwrap_or(1)
 }
 
 fn scrubber_filter(most_common_bit: Option<u32>) -> u32 {
@@ -27,35 +30,72 @@ fn scrubber_filter(most_common_bit: Option<u32>) -> u32 {
     }
 }
 
-pub fn problem1(readings: &[u32], bits: u32) -> u32 {
-    if bits > 32 {
-        panic!("Only support up to 32bit power readings");
-    } else if let Some(index) = readings.iter().position(|r| *r > (1 << bits)) {
-        panic!(
-            "Element {} at index {} uses more than {} bits",
-            readings[index], index, bits
-        );
+pub fn parse_input(input: &str) -> Result<(Vec<u32>, u32), ParseError> {
+    let mut bits = None;
+    let mut readings = Vec::new();
+    for line in non_empty_lines(input) {
+        let digits = line.map_chars(|c| c.to_digit(2))?;
+        let expected_bits = *bits.get_or_insert(digits.len());
+        if digits.len() > 32 {
+            return Err(line.error_at_end(ParseErrorKind::Expected("at most 32 bits")));
+        } else if digits.len() != expected_bits {
+            return Err(line.error_at_end(ParseErrorKind::Expected(
+                "every reading to have the same number of bits",
+            )));
+        }
+
+        readings.push(digits.iter().fold(0u32, |acc, digit| (acc << 1) | digit));
+    }
+
+    match bits {
+        Some(bits) => Ok((readings, bits as u32)),
+        None => Err(ParseError::end_of(input)),
+    }
+}
+
+fn check_readings(readings: &[u32], bits: u32) -> Result<(), AocError> {
+    if readings.is_empty() {
+        return Err(AocError::EmptyInput);
+    } else if bits > 32 {
+        return Err(PuzzleError::TooManyBits { bits, max: 32 }.on_day(3));
     }
 
+    match readings.iter().find(|r| (**r as u64) >> bits != 0) {
+        Some(reading) => Err(PuzzleError::TooManyBits {
+            bits: 32 - reading.leading_zeros(),
+            max: bits,
+        }
+        .on_day(3)),
+        None => Ok(()),
+    }
+}
+
+pub fn problem1(readings: &[u32], bits: u32) -> Result<u32, AocError> {
+    check_readings(readings, bits)?;
+
     let mut gamma = 0u32;
     for bit in 0..bits {
         let count_set = count_with_bit_set(readings, bit);
 
         let half = readings.len() / 2;
-        if count_set == half && readings.len() % 2 == 0 {
-            panic!("Equal number of 0s and 1s");
+        if count_set == half && readings.len().is_multiple_of(2) {
+            return Err(PuzzleError::AmbiguousBit { bit }.on_day(3));
         }
         if count_set > half {
             gamma |= 1 << bit;
         }
     }
 
-    let epsilon_mask = (2u32.pow(bits)) - 1;
+    let epsilon_mask = ((1u64 << bits) - 1) as u32;
     let epsilon = !gamma & epsilon_mask;
-    gamma * epsilon
+    Ok(gamma * epsilon)
 }
 
-fn problem2_impl(readings: &[u32], bits: u32, filter_to_bit: impl Fn(Option<u32>) -> u32) -> u32 {
+fn problem2_impl(
+    readings: &[u32],
+    bits: u32,
+    filter_to_bit: impl Fn(Option<u32>) -> u32,
+) -> Result<u32, AocError> {
     let mut valid_set = readings.to_vec();
     for bit in (0..bits).rev() {
         let count_set = count_with_bit_set(valid_set.as_slice(), bit);
@@ -64,82 +104,91 @@ fn problem2_impl(readings: &[u32], bits: u32, filter_to_bit: impl Fn(Option<u32>
             Ordering::Equal => None,
             Ordering::Greater => Some(0),
         };
-        valid_set = valid_set
-            .into_iter()
-            .filter(|r| (r >> bit) & 1 == filter_to_bit(most_common_bit))
-            .collect();
+        valid_set.retain(|r| (r >> bit) & 1 == filter_to_bit(most_common_bit));
 
         if valid_set.len() == 1 {
-            return valid_set[0];
+            return Ok(valid_set[0]);
         }
     }
 
-    panic!(
-        "Single valid value not found. {} items remain",
-        valid_set.len()
-    );
+    Err(PuzzleError::NoUniqueRating {
+        remaining: valid_set.len(),
+    }
+    .on_day(3))
 }
 
-pub fn problem2(readings: &[u32], bits: u32) -> u32 {
-    if bits > 32 {
-        panic!("Only support up to 32bit power readings");
-    } else if let Some(index) = readings.iter().position(|r| *r > (1 << bits)) {
-        panic!(
-            "Element {} at index {} uses more than {} bits",
-            readings[index], index, bits
-        );
+pub fn problem2(readings: &[u32], bits: u32) -> Result<u32, AocError> {
+    check_readings(readings, bits)?;
+
+    let ogr = problem2_impl(readings, bits, ogr_filter)?;
+    let scrubber = problem2_impl(readings, bits, scrubber_filter)?;
+
+    Ok(ogr * scrubber)
+}
+
+pub struct Day3 {
+    readings: Vec<u32>,
+    bits: u32,
+}
+
+impl Solution for Day3 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        let (readings, bits) = parse_input(input)?;
+        Ok(Day3 { readings, bits })
     }
 
-    let ogr = problem2_impl(readings, bits, ogr_filter);
-    let scrubber = problem2_impl(readings, bits, scrubber_filter);
+    fn part1(&self) -> Result<u32, AocError> {
+        problem1(&self.readings, self.bits)
+    }
 
-    ogr * scrubber
+    fn part2(&self) -> Result<u32, AocError> {
+        problem2(&self.readings, self.bits)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use std::path::Path;
-
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day3";
-
     #[test]
     fn problem1_example() {
         let input = [
             0b00100, 0b11110, 0b10110, 0b10111, 0b10101, 0b01111, 0b00111, 0b11100, 0b10000,
             0b11001, 0b00010, 0b01010,
         ];
-        assert_eq!(problem1(&input, 5), 198);
+        assert_eq!(problem1(&input, 5), Ok(198));
     }
 
     #[test]
-    fn problem1_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-        let lines = reader.lines().collect::<Vec<Result<String, _>>>();
-        let bits = u32::try_from(lines[0].as_ref().unwrap().len()).unwrap();
-        let readings = lines
-            .iter()
-            .map(|line| u32::from_str_radix(line.as_ref().unwrap().as_str(), 2).unwrap())
-            .collect::<Vec<u32>>();
-
-        assert_eq!(problem1(readings.as_slice(), bits), 1540244)
+    fn parse_error() {
+        let err = parse_input("00100\n11110\n1011\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 5));
+
+        let err = parse_input("00100\n11120\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
     }
 
     #[test]
-    fn problem2_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-        let lines = reader.lines().collect::<Vec<Result<String, _>>>();
-        let bits = u32::try_from(lines[0].as_ref().unwrap().len()).unwrap();
-        let readings = lines
-            .iter()
-            .map(|line| u32::from_str_radix(line.as_ref().unwrap().as_str(), 2).unwrap())
-            .collect::<Vec<u32>>();
-
-        assert_eq!(problem2(readings.as_slice(), bits), 4203981)
+    fn problem_errors() {
+        assert_eq!(
+            problem1(&[0b01, 0b10], 2),
+            Err(PuzzleError::AmbiguousBit { bit: 0 }.on_day(3))
+        );
+        assert_eq!(
+            problem1(&[0b01, 0b1000], 2),
+            Err(PuzzleError::TooManyBits { bits: 4, max: 2 }.on_day(3))
+        );
+        assert_eq!(
+            problem2(&[0b01], 33),
+            Err(PuzzleError::TooManyBits { bits: 33, max: 32 }.on_day(3))
+        );
+        assert_eq!(
+            problem2(&[0b01, 0b01], 2),
+            Err(PuzzleError::NoUniqueRating { remaining: 2 }.on_day(3))
+        );
+        assert_eq!(problem1(&[], 2), Err(AocError::EmptyInput));
     }
 }
@@ -1,4 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use aoc_common::error::AocError;
+use aoc_common::parse::{lines, Line, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
+
+use crate::error::PuzzleError;
 
 // the thinking here: an efficient way to check for matches is to compare a bitfield of what has
 // been drawn against a bitfield for every bingo case.
@@ -14,20 +20,36 @@ struct CompiledBoards {
 }
 
 impl CompiledBoards {
-    pub fn from_boards(boards: Vec<Vec<u8>>) -> Self {
+    pub fn from_boards(boards: Vec<Vec<u8>>) -> Result<Self, AocError> {
         let mut bingo_masks: HashMap<u128, usize> =
             HashMap::with_capacity(boards.len() * ROWS * COLUMNS);
         let mut value_masks: Vec<u128> = Vec::with_capacity(boards.len());
         for (board_index, board) in boards.iter().enumerate() {
             let mut board_value_mask = 0u128;
-            assert_eq!(board.len(), ROWS * COLUMNS);
+            let invalid = |reason: String| {
+                PuzzleError::InvalidBoard {
+                    board: board_index,
+                    reason,
+                }
+                .on_day(4)
+            };
+            if board.len() != ROWS * COLUMNS {
+                return Err(invalid(format!(
+                    "expected 25 numbers, found {}",
+                    board.len()
+                )));
+            } else if let Some(value) = board.iter().find(|value| **value >= 100) {
+                return Err(invalid(format!("{} is not a bingo number", value)));
+            }
 
             let row_bingos = board
                 .chunks_exact(ROWS)
                 .map(|chunk| chunk.iter().fold(0u128, |acc, elem| acc | (1 << elem)));
 
             for row_bingo in row_bingos {
-                assert_eq!(board_value_mask & row_bingo, 0);
+                if board_value_mask & row_bingo != 0 || row_bingo.count_ones() != COLUMNS as u32 {
+                    return Err(invalid("numbers appear more than once".to_owned()));
+                }
                 board_value_mask |= row_bingo;
                 bingo_masks.insert(row_bingo, board_index);
             }
@@ -49,10 +71,21 @@ impl CompiledBoards {
             value_masks.push(board_value_mask);
         }
 
-        CompiledBoards {
+        Ok(CompiledBoards {
             bingo_masks,
             value_masks,
-        }
+        })
+    }
+}
+
+/// Draws index into the same 100-bit masks as the boards, so anything larger can't be marked.
+fn check_draws(draws: &[u8]) -> Result<(), AocError> {
+    match draws.iter().find(|draw| **draw >= 100) {
+        Some(draw) => Err(AocError::InvalidInput(format!(
+            "{} is not a bingo number",
+            draw
+        ))),
+        None => Ok(()),
     }
 }
 
@@ -76,8 +109,63 @@ fn sum_unmarked_values(
     unmarked_sum * draw as u32
 }
 
-pub fn problem1(boards: Vec<Vec<u8>>, draws: Vec<u8>) -> u32 {
-    let compiled_boards = CompiledBoards::from_boards(boards);
+fn parse_bingo_number(line: &Line, token: &str) -> Result<u8, ParseError> {
+    match line.parse_number(token)? {
+        value if value < 100 => Ok(value),
+        _ => Err(line.error_at(token, ParseErrorKind::Expected("a number below 100"))),
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<(Vec<Vec<u8>>, Vec<u8>), ParseError> {
+    let mut lines = lines(input);
+
+    let draws_line = lines.next().ok_or_else(|| ParseError::end_of(input))?;
+    let draws = draws_line
+        .text
+        .trim()
+        .split(',')
+        .map(|draw| parse_bingo_number(&draws_line, draw))
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    // boards are separated by blank lines, so group the remaining lines before parsing them
+    let mut board_lines: Vec<Vec<Line>> = vec![];
+    let mut current_lines = vec![];
+    for line in lines {
+        if line.text.trim().is_empty() {
+            if !current_lines.is_empty() {
+                board_lines.push(current_lines);
+                current_lines = vec![];
+            }
+        } else {
+            current_lines.push(line);
+        }
+    }
+    if !current_lines.is_empty() {
+        board_lines.push(current_lines);
+    }
+
+    let mut boards: Vec<Vec<u8>> = Vec::with_capacity(board_lines.len());
+    for lines in board_lines {
+        let mut board: Vec<u8> = Vec::with_capacity(ROWS * COLUMNS);
+        for line in lines.iter() {
+            for value in line.text.split_whitespace() {
+                board.push(parse_bingo_number(line, value)?);
+            }
+        }
+
+        if board.len() != ROWS * COLUMNS {
+            let last_line = lines.last().unwrap();
+            return Err(last_line.error_at_end(ParseErrorKind::Expected("5 rows of 5 numbers")));
+        }
+        boards.push(board);
+    }
+
+    Ok((boards, draws))
+}
+
+pub fn problem1(boards: Vec<Vec<u8>>, draws: Vec<u8>) -> Result<u32, AocError> {
+    check_draws(&draws)?;
+    let compiled_boards = CompiledBoards::from_boards(boards)?;
 
     // now, search for the shortest prefix of |draws| that matches a corresponding bingo mask
     let mut draw_mask = 0u128;
@@ -98,22 +186,27 @@ pub fn problem1(boards: Vec<Vec<u8>>, draws: Vec<u8>) -> u32 {
 
         if let Some(winning_board_index) = found_bingo {
             // cool, we found our bingo. now, we need to sum all of the unmarked numbers
-            return sum_unmarked_values(&compiled_boards, draw_mask, winning_board_index, draw);
+            return Ok(sum_unmarked_values(
+                &compiled_boards,
+                draw_mask,
+                winning_board_index,
+                draw,
+            ));
         }
     }
 
-    panic!();
+    Err(PuzzleError::NoWinner.on_day(4))
 }
 
-pub fn problem2(boards: Vec<Vec<u8>>, draws: Vec<u8>) -> u32 {
-    let count_boards = boards.len() as u32;
-    let compiled_boards = CompiledBoards::from_boards(boards);
+pub fn problem2(boards: Vec<Vec<u8>>, draws: Vec<u8>) -> Result<u32, AocError> {
+    check_draws(&draws)?;
+    let count_boards = boards.len();
+    let compiled_boards = CompiledBoards::from_boards(boards)?;
 
     // search for the board that will win last by remembering which boards we have
     // seen that have already won until we find the last board to win.
     let mut draw_mask = 0u128;
-    let mut winning_board_mask = 0u128;
-    let all_boards_winning_mask = 2u128.pow(count_boards) - 1;
+    let mut winning_boards = HashSet::with_capacity(count_boards);
     for draw in draws {
         draw_mask |= 1 << draw;
 
@@ -130,27 +223,49 @@ pub fn problem2(boards: Vec<Vec<u8>>, draws: Vec<u8>) -> u32 {
                 });
 
         for winning_board_index in winning_board_indexes {
-            winning_board_mask |= 1 << winning_board_index;
-            if winning_board_mask == all_boards_winning_mask {
+            winning_boards.insert(*winning_board_index);
+            if winning_boards.len() == count_boards {
                 // cool, we found our last bingo. now, we need to sum all of the unmarked numbers
-                return sum_unmarked_values(&compiled_boards, draw_mask, winning_board_index, draw);
+                return Ok(sum_unmarked_values(
+                    &compiled_boards,
+                    draw_mask,
+                    winning_board_index,
+                    draw,
+                ));
             }
         }
     }
 
-    panic!();
+    Err(PuzzleError::NoWinner.on_day(4))
+}
+
+pub struct Day4 {
+    boards: Vec<Vec<u8>>,
+    draws: Vec<u8>,
+}
+
+impl Solution for Day4 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        let (boards, draws) = parse_input(input)?;
+        Ok(Day4 { boards, draws })
+    }
+
+    fn part1(&self) -> Result<u32, AocError> {
+        problem1(self.boards.clone(), self.draws.clone())
+    }
+
+    fn part2(&self) -> Result<u32, AocError> {
+        problem2(self.boards.clone(), self.draws.clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use std::path::Path;
-
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day4";
-
     #[test]
     fn problem1_example() {
         let draws: Vec<u8> = vec![
@@ -172,76 +287,52 @@ mod tests {
             ],
         ];
 
-        assert_eq!(problem1(boards, draws), 4512);
+        assert_eq!(problem1(boards, draws), Ok(4512));
     }
 
     #[test]
-    fn problem1_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
-
-        let draws_line = lines.next().unwrap().unwrap();
-        let draws = draws_line
-            .split(',')
-            .map(|draw| draw.parse().unwrap())
-            .collect::<Vec<u8>>();
-
-        // skip a line to get to the first board
-        lines.next();
-
-        let mut boards: Vec<Vec<u8>> = Vec::with_capacity(100);
-        let mut current_board: Vec<u8> = Vec::with_capacity(ROWS * COLUMNS);
-        for line in lines.map(|l| l.unwrap()) {
-            if line.trim().len() == 0 {
-                boards.push(current_board);
-                current_board = Vec::with_capacity(ROWS * COLUMNS);
-                continue;
-            }
-
-            for value in line
-                .split(char::is_whitespace)
-                .filter(|v| v.trim().len() > 0)
-            {
-                current_board.push(value.parse().unwrap());
-            }
-        }
-
-        assert_eq!(problem1(boards, draws), 38913);
+    fn problem_errors() {
+        let board: Vec<u8> = (0..25).collect();
+        assert_eq!(
+            problem1(vec![board.clone()], vec![0, 1, 2, 3]),
+            Err(PuzzleError::NoWinner.on_day(4))
+        );
+        assert_eq!(
+            problem2(vec![board.clone(), board], vec![0, 1, 2, 3]),
+            Err(PuzzleError::NoWinner.on_day(4))
+        );
+
+        let mut duplicate: Vec<u8> = (0..25).collect();
+        duplicate[24] = 0;
+        assert!(matches!(
+            problem1(vec![duplicate], vec![0]).unwrap_err().day_error(),
+            Some(PuzzleError::InvalidBoard { board: 0, .. })
+        ));
     }
 
     #[test]
-    fn problem2_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
-
-        let draws_line = lines.next().unwrap().unwrap();
-        let draws = draws_line
-            .split(',')
-            .map(|draw| draw.parse().unwrap())
-            .collect::<Vec<u8>>();
-
-        // skip a line to get to the first board
-        lines.next();
-
-        let mut boards: Vec<Vec<u8>> = Vec::with_capacity(100);
-        let mut current_board: Vec<u8> = Vec::with_capacity(ROWS * COLUMNS);
-        for line in lines.map(|l| l.unwrap()) {
-            if line.trim().len() == 0 {
-                boards.push(current_board);
-                current_board = Vec::with_capacity(ROWS * COLUMNS);
-                continue;
-            }
-
-            for value in line
-                .split(char::is_whitespace)
-                .filter(|v| v.trim().len() > 0)
-            {
-                current_board.push(value.parse().unwrap());
-            }
+    fn out_of_range_draws() {
+        let board: Vec<u8> = (0..25).collect();
+        for draw in [100, 127, 128, 255] {
+            assert!(matches!(
+                problem1(vec![board.clone()], vec![0, draw]),
+                Err(AocError::InvalidInput(_))
+            ));
+            assert!(matches!(
+                problem2(vec![board.clone()], vec![draw, 0]),
+                Err(AocError::InvalidInput(_))
+            ));
         }
+    }
 
-        assert_eq!(problem2(boards, draws), 16836);
+    #[test]
+    fn parse_error() {
+        let input =
+            "7,4,9\n\n22 13 17 11  0\n 8  2 23  4 24\n21  9 14 16  7\n 6 10  3 18  5\n 1 12 20 15";
+        let err = parse_input(input).unwrap_err();
+        assert_eq!((err.line, err.column), (7, 12));
+
+        let err = parse_input("7,4,x9\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
     }
 }
@@ -1,71 +1,69 @@
-use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::iter;
+use std::str::FromStr;
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, PartialOrd, Ord)]
-pub struct Point {
-    x: u32,
-    y: u32,
+use aoc_common::coord::Coord;
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, Line, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
+
+#[derive(Clone, Debug)]
+pub struct Vent {
+    start: Coord,
+    end: Coord,
 }
 
-impl Point {
-    pub fn range_to(&self, dest: &Point) -> impl Iterator<Item = Point> {
-        let range_x: Vec<u32> = match self.x.cmp(&dest.x) {
-            Ordering::Less => (self.x..=dest.x).collect(),
-            Ordering::Equal => iter::repeat(self.x)
-                .take((dest.y as i32 - self.y as i32).abs() as usize + 1)
-                .collect(),
-            Ordering::Greater => (dest.x..=self.x).rev().collect(),
-        };
-        let range_y: Vec<u32> = match self.y.cmp(&dest.y) {
-            Ordering::Less => (self.y..=dest.y).collect(),
-            Ordering::Equal => iter::repeat(self.y)
-                .take((dest.x as i32 - self.x as i32).abs() as usize + 1)
-                .collect(),
-            Ordering::Greater => (dest.y..=self.y).rev().collect(),
-        };
-
-        range_x
-            .into_iter()
-            .zip(range_y)
-            .map(|p| Self { x: p.0, y: p.1 })
+impl Vent {
+    pub fn new(start_x: i32, start_y: i32, end_x: i32, end_y: i32) -> Self {
+        Vent {
+            start: Coord::from_xy(start_x, start_y),
+            end: Coord::from_xy(end_x, end_y),
+        }
     }
 
-    pub fn is_diagonal_to(&self, dest: &Point) -> bool {
-        (self.x as i32 - dest.x as i32).abs() == (self.y as i32 - dest.y as i32).abs()
+    fn is_straight(&self) -> bool {
+        self.start.row == self.end.row || self.start.col == self.end.col
     }
 }
 
-pub struct Vent {
-    start: Point,
-    end: Point,
+fn parse_point(line: &Line, token: &str) -> Result<Coord, ParseError> {
+    let (x, y) = token
+        .split_once(',')
+        .ok_or_else(|| line.error_at(token, ParseErrorKind::Expected("a point like `x,y`")))?;
+    Ok(Coord::from_xy(line.parse_number(x)?, line.parse_number(y)?))
 }
 
-impl Vent {
-    pub fn new(start_x: u32, start_y: u32, end_x: u32, end_y: u32) -> Self {
-        Vent {
-            start: Point {
-                x: start_x,
-                y: start_y,
-            },
-            end: Point { x: end_x, y: end_y },
-        }
+impl FromStr for Vent {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line { number: 1, text: s };
+        let (start, end) = line.split_once(" -> ", "`->`")?;
+        Ok(Vent {
+            start: parse_point(&line, start)?,
+            end: parse_point(&line, end)?,
+        })
     }
 }
 
+pub fn parse_input(input: &str) -> Result<Vec<Vent>, ParseError> {
+    non_empty_lines(input)
+        .map(|line| line.parse(line.text.trim()))
+        .collect()
+}
+
 pub fn problem1(vents: Vec<Vent>) -> usize {
     let vent_points = vents
         .into_iter()
         .filter_map(|v| {
-            if v.start.x == v.end.x || v.start.y == v.end.y {
-                Some(v.start.range_to(&v.end))
+            if v.is_straight() {
+                Some(v.start.line_to(v.end))
             } else {
                 None
             }
         })
         .flatten();
 
-    let mut seen_points = HashMap::<Point, u32>::new();
+    let mut seen_points = HashMap::<Coord, u32>::new();
     for point in vent_points {
         *seen_points.entry(point).or_insert(0) += 1;
     }
@@ -77,15 +75,15 @@ pub fn problem2(vents: Vec<Vent>) -> usize {
     let vent_points = vents
         .into_iter()
         .filter_map(|v| {
-            if v.start.x == v.end.x || v.start.y == v.end.y || v.start.is_diagonal_to(&v.end) {
-                Some(v.start.range_to(&v.end))
+            if v.start.is_aligned_with(&v.end) {
+                Some(v.start.line_to(v.end))
             } else {
                 None
             }
         })
         .flatten();
 
-    let mut seen_points = HashMap::<Point, u32>::new();
+    let mut seen_points = HashMap::<Coord, u32>::new();
     for point in vent_points {
         *seen_points.entry(point).or_insert(0) += 1;
     }
@@ -93,17 +91,32 @@ pub fn problem2(vents: Vec<Vent>) -> usize {
     seen_points.values().filter(|seen| **seen > 1).count()
 }
 
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use std::path::Path;
+pub struct Day5 {
+    vents: Vec<Vent>,
+}
 
-    use regex::Regex;
+impl Solution for Day5 {
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    use super::*;
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day5 {
+            vents: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<usize, AocError> {
+        Ok(problem1(self.vents.clone()))
+    }
+
+    fn part2(&self) -> Result<usize, AocError> {
+        Ok(problem2(self.vents.clone()))
+    }
+}
 
-    const DATA_PATH: &'static str = "data/day5";
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn problem1_example() {
@@ -120,53 +133,16 @@ mod tests {
             Vent::new(5, 5, 8, 2),
         ];
 
-        assert_eq!(problem1(vents), 5);
+        assert_eq!(problem1(vents.clone()), 5);
+        assert_eq!(problem2(vents), 12);
     }
 
     #[test]
-    fn problem1_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-
-        let line_re = Regex::new(r"^(\d+),(\d+) -> (\d+),(\d+)$").unwrap();
-        let vents = reader
-            .lines()
-            .map(|line| {
-                let line = line.unwrap();
-                let captures = line_re.captures(line.as_str()).unwrap();
-                // println!("{:?}", captures);
-                Vent::new(
-                    captures[1].parse().unwrap(),
-                    captures[2].parse().unwrap(),
-                    captures[3].parse().unwrap(),
-                    captures[4].parse().unwrap(),
-                )
-            })
-            .collect();
-
-        assert_eq!(problem1(vents), 5092);
-    }
+    fn parse_error() {
+        let err = parse_input("0,9 -> 5,9\n8,0 -> 0;8\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 8));
 
-    #[test]
-    fn problem2_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let reader = BufReader::new(file);
-
-        let line_re = Regex::new(r"^(\d+),(\d+) -> (\d+),(\d+)$").unwrap();
-        let vents = reader
-            .lines()
-            .map(|line| {
-                let line = line.unwrap();
-                let captures = line_re.captures(line.as_str()).unwrap();
-                Vent::new(
-                    captures[1].parse().unwrap(),
-                    captures[2].parse().unwrap(),
-                    captures[3].parse().unwrap(),
-                    captures[4].parse().unwrap(),
-                )
-            })
-            .collect();
-
-        assert_eq!(problem2(vents), 20484);
+        let err = parse_input("0,9 -> 5,9\n8,0 0,8\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 8));
     }
 }
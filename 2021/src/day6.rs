@@ -1,8 +1,34 @@
-pub fn problem1(initial_fish_timers: &[u8], days: u32) -> u64 {
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
+
+pub fn parse_input(input: &str) -> Result<Vec<u8>, ParseError> {
+    match non_empty_lines(input).next() {
+        Some(line) => line
+            .text
+            .trim()
+            .split(',')
+            .map(|col| match line.parse_number(col)? {
+                timer if timer < 9 => Ok(timer),
+                _ => Err(line.error_at(col, ParseErrorKind::Expected("a timer from 0 to 8"))),
+            })
+            .collect(),
+        None => Ok(vec![]),
+    }
+}
+
+pub fn problem1(initial_fish_timers: &[u8], days: u32) -> Result<u64, AocError> {
     let mut fish_per_timer: [u64; 9] = [0, 0, 0, 0, 0, 0, 0, 0, 0];
     for fish_timer in initial_fish_timers {
-        assert!(*fish_timer < 9);
-        fish_per_timer[*fish_timer as usize] += 1;
+        match fish_per_timer.get_mut(*fish_timer as usize) {
+            Some(count) => *count += 1,
+            None => {
+                return Err(AocError::InvalidInput(format!(
+                    "fish timer {} is above 8",
+                    fish_timer
+                )))
+            }
+        }
     }
 
     for _ in 0..days {
@@ -10,53 +36,56 @@ pub fn problem1(initial_fish_timers: &[u8], days: u32) -> u64 {
         fish_per_timer[6] += fish_per_timer[8];
     }
 
-    fish_per_timer.iter().sum()
+    Ok(fish_per_timer.iter().sum())
+}
+
+pub struct Day6 {
+    timers: Vec<u8>,
+}
+
+impl Solution for Day6 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day6 {
+            timers: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<u64, AocError> {
+        problem1(&self.timers, 80)
+    }
+
+    fn part2(&self) -> Result<u64, AocError> {
+        problem1(&self.timers, 256)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::{BufReader, Read};
-    use std::path::Path;
-
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day6";
-
     #[test]
     fn problem1_example() {
         let initial_state: [u8; 5] = [3, 4, 3, 1, 2];
-        assert_eq!(problem1(&initial_state[..], 18), 26);
-        assert_eq!(problem1(&initial_state[..], 80), 5934);
+        assert_eq!(problem1(&initial_state[..], 18), Ok(26));
+        assert_eq!(problem1(&initial_state[..], 80), Ok(5934));
     }
 
     #[test]
-    fn problem1_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let mut reader = BufReader::new(file);
-
-        let mut file_content = String::new();
-        reader.read_to_string(&mut file_content).unwrap();
-
-        let initial_state: Vec<u8> = file_content
-            .split(',')
-            .map(|col| col.parse().unwrap())
-            .collect();
-        assert_eq!(problem1(initial_state.as_slice(), 80), 358214);
+    fn problem_errors() {
+        assert!(matches!(
+            problem1(&[3, 4, 9], 18),
+            Err(AocError::InvalidInput(_))
+        ));
     }
 
     #[test]
-    fn problem2_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let mut reader = BufReader::new(file);
+    fn parse_error() {
+        assert_eq!(parse_input("3,4,3,1,2\n").unwrap(), vec![3, 4, 3, 1, 2]);
 
-        let mut file_content = String::new();
-        reader.read_to_string(&mut file_content).unwrap();
-
-        let initial_state: Vec<u8> = file_content
-            .split(',')
-            .map(|col| col.parse().unwrap())
-            .collect();
-        assert_eq!(problem1(initial_state.as_slice(), 256), 1622533344325);
+        let err = parse_input("3,4,9,1,2").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
     }
 }
@@ -1,75 +1,112 @@
-pub fn problem1(positions: &[u32]) -> u32 {
-    let min_position = *positions.iter().min().unwrap();
-    let max_position = *positions.iter().max().unwrap();
-
-    (min_position..=max_position)
-        .map(|candidate| {
-            positions.iter().fold(0u32, |cur, elem| {
-                cur + (*elem as i32 - candidate as i32).abs() as u32
-            })
-        })
-        .min()
-        .unwrap()
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, ParseError};
+use aoc_common::solution::Solution;
+
+pub fn parse_input(input: &str) -> Result<Vec<u32>, ParseError> {
+    match non_empty_lines(input).next() {
+        Some(line) => line
+            .text
+            .trim()
+            .split(',')
+            .map(|col| line.parse_number(col))
+            .collect(),
+        None => Ok(vec![]),
+    }
+}
+
+/// The fuel needed to move every crab to |target|, where |cost| gives the fuel for a distance.
+fn total_fuel(
+    positions: &[u32],
+    target: u32,
+    cost: impl Fn(u64) -> Option<u64>,
+) -> Result<u64, AocError> {
+    positions.iter().try_fold(0u64, |total, position| {
+        cost(position.abs_diff(target).into())
+            .and_then(|fuel| total.checked_add(fuel))
+            .ok_or_else(|| AocError::InvalidInput("Fuel total overflows 64 bits".to_owned()))
+    })
+}
+
+fn minimum_fuel(positions: &[u32], cost: impl Fn(u64) -> Option<u64>) -> Result<u64, AocError> {
+    let min_position = *positions.iter().min().ok_or(AocError::EmptyInput)?;
+    let max_position = *positions.iter().max().ok_or(AocError::EmptyInput)?;
+
+    (min_position..=max_position).try_fold(u64::MAX, |best, candidate| {
+        Ok(best.min(total_fuel(positions, candidate, &cost)?))
+    })
+}
+
+pub fn problem1(positions: &[u32]) -> Result<u64, AocError> {
+    minimum_fuel(positions, Some)
+}
+
+/// Each step costs one more than the last, so |distance| steps cost its triangular number.
+fn triangular_fuel(distance: u64) -> Option<u64> {
+    distance
+        .checked_mul(distance + 1)
+        .map(|triangle| triangle / 2)
+}
+
+pub fn problem2(positions: &[u32]) -> Result<u64, AocError> {
+    minimum_fuel(positions, triangular_fuel)
 }
 
-pub fn problem2(positions: &[u32]) -> u32 {
-    let min_position = *positions.iter().min().unwrap();
-    let max_position = *positions.iter().max().unwrap();
+pub struct Day7 {
+    positions: Vec<u32>,
+}
+
+impl Solution for Day7 {
+    type Answer1 = u64;
+    type Answer2 = u64;
 
-    (min_position..=max_position)
-        .map(|candidate| {
-            positions.iter().fold(0u32, |cur, elem| {
-                let distance = (*elem as i32 - candidate as i32).abs() as u32;
-                cur + ((distance * (distance + 1)) / 2)
-            })
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day7 {
+            positions: parse_input(input)?,
         })
-        .min()
-        .unwrap()
+    }
+
+    fn part1(&self) -> Result<u64, AocError> {
+        problem1(&self.positions)
+    }
+
+    fn part2(&self) -> Result<u64, AocError> {
+        problem2(&self.positions)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::{BufReader, Read};
-    use std::path::Path;
-
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day7";
-
     #[test]
     fn problem1_example() {
         let initial_state: [u32; 10] = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
-        assert_eq!(problem1(&initial_state[..]), 37);
+        assert_eq!(problem1(&initial_state[..]), Ok(37));
     }
 
     #[test]
-    fn problem1_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let mut reader = BufReader::new(file);
-
-        let mut file_content = String::new();
-        reader.read_to_string(&mut file_content).unwrap();
+    fn widely_spaced_crabs() {
+        assert_eq!(problem1(&[0, 100_000]), Ok(100_000));
+        assert_eq!(problem2(&[0, 100_000]), Ok(2_500_050_000));
 
-        let initial_state: Vec<u32> = file_content
-            .split(',')
-            .map(|col| col.parse().unwrap())
-            .collect();
-        assert_eq!(problem1(initial_state.as_slice()), 352331);
+        let around_i32_max = [i32::MAX as u32 - 1, i32::MAX as u32 + 1];
+        assert_eq!(problem1(&around_i32_max), Ok(2));
+        assert_eq!(problem2(&around_i32_max), Ok(2));
     }
 
     #[test]
-    fn problem2_real() {
-        let file = File::open(Path::new(DATA_PATH)).unwrap();
-        let mut reader = BufReader::new(file);
-
-        let mut file_content = String::new();
-        reader.read_to_string(&mut file_content).unwrap();
+    fn problem_errors() {
+        assert_eq!(problem1(&[]), Err(AocError::EmptyInput));
+        assert_eq!(problem2(&[]), Err(AocError::EmptyInput));
+        assert!(matches!(
+            total_fuel(&[u32::MAX; 3], 0, triangular_fuel),
+            Err(AocError::InvalidInput(_))
+        ));
+    }
 
-        let initial_state: Vec<u32> = file_content
-            .split(',')
-            .map(|col| col.parse().unwrap())
-            .collect();
-        assert_eq!(problem2(initial_state.as_slice()), 99266250);
+    #[test]
+    fn parse_error() {
+        let err = parse_input("16,1,2,-3").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 8));
     }
 }
@@ -1,7 +1,12 @@
 use std::collections::HashMap;
 
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, Line, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
 use itertools::Itertools;
 
+use crate::error::PuzzleError;
+
 trait Single {
     type Item;
     fn single(&self) -> Result<&Self::Item, ()>;
@@ -18,6 +23,43 @@ impl<T> Single for Vec<T> {
     }
 }
 
+fn parse_patterns(line: &Line, patterns: &str, count: usize) -> Result<String, ParseError> {
+    let patterns = patterns.trim();
+    let mut found = 0;
+    for pattern in patterns.split(' ') {
+        if let Some(offset) = pattern.find(|c| !('a'..='g').contains(&c)) {
+            let c = pattern[offset..].chars().next().unwrap();
+            return Err(line.error_at(&pattern[offset..], ParseErrorKind::UnexpectedChar(c)));
+        } else if !(2..=7).contains(&pattern.len()) {
+            return Err(line.error_at(pattern, ParseErrorKind::Expected("2 to 7 segments")));
+        }
+        found += 1;
+    }
+
+    if found != count {
+        let expected = if count == 10 {
+            "10 signal patterns"
+        } else {
+            "4 output values"
+        };
+        return Err(line.error_at(patterns, ParseErrorKind::Expected(expected)));
+    }
+
+    Ok(patterns.to_owned())
+}
+
+pub fn parse_input(input: &str) -> Result<HashMap<String, String>, ParseError> {
+    non_empty_lines(input)
+        .map(|line| {
+            let (signal, output) = line.split_once(" | ", "`|`")?;
+            Ok((
+                parse_patterns(&line, signal, 10)?,
+                parse_patterns(&line, output, 4)?,
+            ))
+        })
+        .collect()
+}
+
 pub fn problem1(entries: HashMap<String, String>) -> usize {
     let is_unique_length: [bool; 8] = [false, false, true, true, true, false, false, true];
     entries
@@ -25,41 +67,57 @@ pub fn problem1(entries: HashMap<String, String>) -> usize {
         .map(|entry| {
             entry
                 .split(' ')
-                .filter(|digit| {
-                    let digit_length = digit.len();
-                    assert!(digit_length < 8);
-                    is_unique_length[digit_length]
-                })
+                .filter(|digit| is_unique_length.get(digit.len()).copied().unwrap_or(false))
                 .count()
         })
         .sum()
 }
 
-pub fn problem2(entries: HashMap<String, String>) -> u32 {
+pub fn problem2(entries: HashMap<String, String>) -> Result<u32, AocError> {
     entries
         .iter()
-        .map(|(signal, output)| {
+        .map(|(signal, output)| -> Result<u32, AocError> {
+            let undecodable = || {
+                PuzzleError::UndecodableDisplay {
+                    signal: signal.clone(),
+                }
+                .on_day(8)
+            };
+
             // Convert each encoded value to a bitfield -- if `a` is in the value, set the 0th bit,
             // if `b` is in the value, set the 1st bit, and so on.
             let bitfield_values_for_lengths = signal
                 .split(' ')
                 .map(|signal_entry| {
-                    let mask = get_bitfield_for_encoded(signal_entry);
-                    (mask.count_ones() as u8, mask)
+                    let mask = get_bitfield_for_encoded(signal_entry)?;
+                    Ok((mask.count_ones() as u8, mask))
                 })
+                .collect::<Result<Vec<_>, AocError>>()?
+                .into_iter()
                 .into_group_map();
+            let single = |length: u8| {
+                bitfield_values_for_lengths
+                    .get(&length)
+                    .and_then(|masks| masks.single().ok())
+                    .copied()
+                    .ok_or_else(undecodable)
+            };
+            let three = |length: u8| match bitfield_values_for_lengths.get(&length) {
+                Some(masks) if masks.len() == 3 => Ok(masks),
+                _ => Err(undecodable()),
+            };
 
             // the indexes here are the real numbers that the bitfield values represent
             let mut bitfield_values: [u8; 10] = [
                 0,
-                *bitfield_values_for_lengths[&2].single().unwrap(),
+                single(2)?,
                 0,
                 0,
-                *bitfield_values_for_lengths[&4].single().unwrap(),
+                single(4)?,
                 0,
                 0,
-                *bitfield_values_for_lengths[&3].single().unwrap(),
-                *bitfield_values_for_lengths[&7].single().unwrap(),
+                single(3)?,
+                single(7)?,
                 0,
             ];
 
@@ -75,8 +133,7 @@ pub fn problem2(entries: HashMap<String, String>) -> u32 {
             // bitstrings[4] & bitstrings[9] == bitstrings[4]. We can use these properties to
             // determine which of the unknown 5 and 6-length bitstrings map to which actual values.
 
-            assert_eq!(bitfield_values_for_lengths[&6].len(), 3);
-            for &mask in bitfield_values_for_lengths[&6].iter() {
+            for &mask in three(6)?.iter() {
                 if mask & bitfield_values[4] == bitfield_values[4] {
                     bitfield_values[9] = mask;
                 } else if mask & bitfield_values[7] == bitfield_values[7] {
@@ -86,8 +143,7 @@ pub fn problem2(entries: HashMap<String, String>) -> u32 {
                 }
             }
 
-            assert_eq!(bitfield_values_for_lengths[&5].len(), 3);
-            for &mask in bitfield_values_for_lengths[&5].iter() {
+            for &mask in three(5)?.iter() {
                 if mask & bitfield_values[7] == bitfield_values[7] {
                     bitfield_values[3] = mask;
                 } else if (mask ^ bitfield_values[6]).count_ones() == 1 {
@@ -98,7 +154,9 @@ pub fn problem2(entries: HashMap<String, String>) -> u32 {
             }
 
             // at this point, every digit should have a corresponding bitfield value
-            assert!(!bitfield_values.iter().any(|&mask| mask == 0));
+            if bitfield_values.contains(&0) {
+                return Err(undecodable());
+            }
 
             // map the output for this entry to a decimal value
             output
@@ -107,40 +165,55 @@ pub fn problem2(entries: HashMap<String, String>) -> u32 {
                 .map(get_bitfield_for_encoded)
                 .enumerate()
                 .map(|(power, bitfield)| {
-                    let real_digit = bitfield_values.iter().position(|&d| d == bitfield).unwrap();
-                    real_digit as u32 * 10u32.pow(power as u32)
+                    let bitfield = bitfield?;
+                    let real_digit = bitfield_values
+                        .iter()
+                        .position(|&d| d == bitfield)
+                        .ok_or_else(undecodable)?;
+                    Ok(real_digit as u32 * 10u32.pow(power as u32))
                 })
-                .sum::<u32>()
+                .sum()
         })
         .sum()
 }
 
-fn get_bitfield_for_encoded(encoded: &str) -> u8 {
-    encoded
-        .chars()
-        .fold(0, |mask, c| mask | (1 << c as u8 - 'a' as u8))
+fn get_bitfield_for_encoded(encoded: &str) -> Result<u8, AocError> {
+    encoded.chars().try_fold(0, |mask, c| match c {
+        'a'..='g' => Ok(mask | (1 << (c as u8 - b'a'))),
+        _ => Err(AocError::InvalidInput(format!(
+            "{:?} is not a display segment",
+            c
+        ))),
+    })
+}
+
+pub struct Day8 {
+    entries: HashMap<String, String>,
+}
+
+impl Solution for Day8 {
+    type Answer1 = usize;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day8 {
+            entries: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<usize, AocError> {
+        Ok(problem1(self.entries.clone()))
+    }
+
+    fn part2(&self) -> Result<u32, AocError> {
+        problem2(self.entries.clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day8";
-
-    fn parse_input(content: &str) -> HashMap<String, String> {
-        content
-            .split('\n')
-            .map(|line| {
-                let separated: Vec<&str> = line.split(" | ").collect();
-                assert_eq!(separated.len(), 2, "{}", line);
-                (
-                    separated[0].trim().to_owned(),
-                    separated[1].trim().to_owned(),
-                )
-            })
-            .collect()
-    }
-
     #[test]
     fn problem1_example() {
         let example =
@@ -155,16 +228,48 @@ bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbg
 egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
 gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
 
-        let entries = parse_input(example);
+        let entries = parse_input(example).unwrap();
 
         assert_eq!(problem1(entries), 26);
     }
 
     #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let entries = parse_input(&content);
-        assert_eq!(problem1(entries), 534);
+    fn problem2_undecodable() {
+        // every pattern here is two segments long, so there is no unique 4, 7 or 8
+        let signal = "ab ac ad ae af ag bc bd be bf".to_owned();
+        let entries = HashMap::from([(signal.clone(), "ab ac ad ae".to_owned())]);
+        assert_eq!(
+            problem2(entries),
+            Err(PuzzleError::UndecodableDisplay { signal }.on_day(8))
+        );
+    }
+
+    #[test]
+    fn problem2_invalid_segments() {
+        for signal in [
+            "ab ac ad ae af ag bc bd be bh",
+            "ab ac ad ae af ag bc bd be B",
+        ] {
+            let entries = HashMap::from([(signal.to_owned(), "ab".to_owned())]);
+            assert!(matches!(problem2(entries), Err(AocError::InvalidInput(_))));
+        }
+
+        let signal = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        let entries = HashMap::from([(signal.to_owned(), "cdfeb fcadb cdfeb cdbaz".to_owned())]);
+        assert!(matches!(problem2(entries), Err(AocError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn parse_error() {
+        let err = parse_input(
+            "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb fdgacbe cefdb cefbgd gcbe",
+        )
+        .unwrap_err();
+        assert_eq!((err.line, err.column), (1, 85));
+
+        let err = parse_input("be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbx").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnexpectedChar('x'));
+        assert_eq!((err.line, err.column), (1, 86));
     }
 
     #[test]
@@ -181,15 +286,8 @@ bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbg
 egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
 gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
 
-        let entries = parse_input(example);
+        let entries = parse_input(example).unwrap();
 
-        assert_eq!(problem2(entries), 61229);
-    }
-
-    #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let entries = parse_input(&content);
-        assert_eq!(problem2(entries), 1070188);
+        assert_eq!(problem2(entries), Ok(61229));
     }
 }
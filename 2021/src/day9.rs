@@ -1,163 +1,111 @@
-use std::{
-    collections::{BinaryHeap, VecDeque},
-    iter,
-};
-
-fn start_index(index: usize) -> usize {
-    match index {
-        0 => 0,
-        _ => index - 1,
-    }
-}
-
-fn end_index(length: usize, index: usize) -> usize {
-    match index {
-        x if length == 0 || x >= length - 1 => x,
-        _ => index + 1,
-    }
-}
+use std::collections::BinaryHeap;
 
-#[derive(Debug, Clone, Copy)]
-struct Coord {
-    pub row: usize,
-    pub col: usize,
-}
-
-fn surrounding_coords<'a, T>(
-    height_map: &'a Vec<Vec<T>>,
-    pos: &'a Coord,
-) -> impl Iterator<Item = Coord> + 'a {
-    (start_index(pos.row)..=end_index(height_map.len(), pos.row))
-        .flat_map(|row| {
-            (start_index(pos.col)..=end_index(height_map[row].len(), pos.col))
-                .map(|col| Coord { row, col })
-                .collect::<Vec<_>>()
-        })
-        .filter(|coord| coord.col != pos.col || coord.row != pos.row)
-}
+use aoc_common::coord::{Coord, Moore, Neighborhood, VonNeumann};
+use aoc_common::error::AocError;
+use aoc_common::grid::Grid;
+use aoc_common::parse::{self, ParseError};
+use aoc_common::search::{self, Graph};
+use aoc_common::solution::Solution;
 
-fn basin_coords<'a, T>(
-    height_map: &'a Vec<Vec<T>>,
-    pos: &'a Coord,
-) -> impl Iterator<Item = Coord> + 'a {
-    (start_index(pos.row)..=end_index(height_map.len(), pos.row))
-        .flat_map(|row| {
-            (start_index(pos.col)..=end_index(height_map[row].len(), pos.col))
-                .map(|col| Coord { row, col })
-                .collect::<Vec<_>>()
-        })
-        .filter(|coord| (coord.col != pos.col) ^ (coord.row != pos.row))
+pub fn parse_input(input: &str) -> Result<Grid<u8>, ParseError> {
+    parse::grid(input, |c| c.to_digit(10).map(|height| height as u8))
 }
 
-pub fn problem1(height_map: Vec<Vec<u8>>) -> u32 {
+/// Sums the risk levels of the points that are lower than all of their neighbors.
+pub fn sum_low_points(height_map: &Grid<u8>, neighborhood: &impl Neighborhood) -> u32 {
     let mut sum_low_point_height = 0u32;
-    for (row_index, row) in height_map.iter().enumerate() {
-        for (col_index, &height) in row.iter().enumerate() {
-            let min_surrounding_value = surrounding_coords(
-                &height_map,
-                &Coord {
-                    row: row_index,
-                    col: col_index,
-                },
-            )
-            .map(|coord| height_map[coord.row][coord.col])
-            .min()
-            .unwrap();
-
-            if min_surrounding_value > height {
-                sum_low_point_height += height as u32 + 1;
-            }
+    for (coord, &height) in height_map.iter() {
+        let is_low_point = height_map
+            .neighbors(neighborhood, coord)
+            .all(|neighbor| height_map[neighbor] > height);
+
+        if is_low_point {
+            sum_low_point_height += height as u32 + 1;
         }
     }
 
     sum_low_point_height
 }
 
-fn search(
-    height_map: &Vec<Vec<u8>>,
-    start: Coord,
-    basin_id: u32,
-    basin_id_map: &mut Vec<Vec<Option<u32>>>,
-) -> u32 {
-    let mut q = VecDeque::new();
-    q.push_back(start);
-    let mut basin_size = 0u32;
-
-    while let Some(coord) = q.pop_front() {
-        if height_map[coord.row][coord.col] == 9 {
-            continue;
-        } else if basin_id_map[coord.row][coord.col].is_some() {
+pub fn problem1(height_map: Grid<u8>) -> u32 {
+    sum_low_points(&height_map, &Moore)
+}
+
+/// The points that are not a peak of height 9, linked to the neighboring points they flow to.
+fn basin_graph<'a>(
+    height_map: &'a Grid<u8>,
+    neighborhood: &'a impl Neighborhood,
+) -> impl Graph<Node = Coord, Cost = u32> + 'a {
+    search::from_fn(move |coord: &Coord| {
+        height_map
+            .neighbors(neighborhood, *coord)
+            .filter(move |neighbor| height_map[*neighbor] < 9)
+            .map(|neighbor| (neighbor, 1))
+    })
+}
+
+/// Multiplies the sizes of the three largest basins, where a basin is the points that flow
+/// down to a low point through |neighborhood|.
+pub fn multiply_largest_basins(
+    height_map: &Grid<u8>,
+    neighborhood: &impl Neighborhood,
+) -> Result<u32, AocError> {
+    let basins = basin_graph(height_map, neighborhood);
+    let mut in_basin = height_map.map(|_| false);
+    let mut basin_sizes = BinaryHeap::new();
+
+    for (coord, &height) in height_map.iter() {
+        if height > 9 {
+            return Err(AocError::InvalidInput(format!(
+                "height {} is above 9",
+                height
+            )));
+        }
+        if height == 9 || in_basin[coord] {
             continue;
         }
 
-        basin_id_map[coord.row][coord.col] = Some(basin_id);
-        basin_size += 1;
-
-        for candidate in basin_coords(&height_map, &coord) {
-            if basin_id_map[candidate.row][candidate.col].is_none()
-                && height_map[candidate.row][candidate.col] < 9
-            {
-                q.push_back(candidate);
-            }
+        let basin = search::bfs(&basins, coord, |_| false);
+        for point in basin.reached() {
+            in_basin[point] = true;
         }
+        basin_sizes.push(basin.reached_count() as u32);
     }
 
-    basin_size
+    Ok(basin_sizes.into_sorted_vec().iter().rev().take(3).product())
 }
 
-pub fn problem2(height_map: Vec<Vec<u8>>) -> u32 {
-    let mut next_basin_id = 0u32;
-    let mut basin_id_map: Vec<Vec<Option<u32>>> =
-        iter::repeat(iter::repeat(None).take(height_map[0].len()).collect())
-            .take(height_map.len())
-            .collect();
-    let mut basin_sizes = BinaryHeap::new();
+pub fn problem2(height_map: Grid<u8>) -> Result<u32, AocError> {
+    multiply_largest_basins(&height_map, &VonNeumann)
+}
 
-    for row_index in 0..height_map.len() {
-        for col_index in 0..height_map[row_index].len() {
-            let height = height_map[row_index][col_index];
-            assert!(height <= 9);
-
-            match basin_id_map[row_index][col_index] {
-                Some(_) => continue,
-                None if height == 9 => continue,
-                _ => {
-                    let basin_size = search(
-                        &height_map,
-                        Coord {
-                            row: row_index,
-                            col: col_index,
-                        },
-                        next_basin_id,
-                        &mut basin_id_map,
-                    );
-                    next_basin_id += 1;
-                    basin_sizes.push(basin_size);
-                }
-            }
-        }
+pub struct Day9 {
+    height_map: Grid<u8>,
+}
+
+impl Solution for Day9 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day9 {
+            height_map: parse_input(input)?,
+        })
     }
 
-    basin_sizes.iter().take(3).product()
+    fn part1(&self) -> Result<u32, AocError> {
+        Ok(problem1(self.height_map.clone()))
+    }
+
+    fn part2(&self) -> Result<u32, AocError> {
+        problem2(self.height_map.clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day9";
-
-    fn parse_input(input: &str) -> Vec<Vec<u8>> {
-        input
-            .split('\n')
-            .map(|line| {
-                line.trim()
-                    .chars()
-                    .map(|c| c.to_digit(10).unwrap() as u8)
-                    .collect()
-            })
-            .collect()
-    }
     #[test]
     fn problem1_example() {
         let example = "2199943210
@@ -165,22 +113,33 @@ mod tests {
 9856789892
 8767896789
 9899965678";
-        let height_map = parse_input(example);
+        let height_map = parse_input(example).unwrap();
 
         assert_eq!(problem1(height_map), 15);
     }
 
     #[test]
-    fn problem1_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let height_map = parse_input(&content);
-        assert_eq!(problem1(height_map), 560);
+    fn neighborhoods() {
+        let height_map = parse_input(
+            "2199943210
+3987894921
+9856789892
+8767896789
+9899965678",
+        )
+        .unwrap();
+
+        assert_eq!(sum_low_points(&height_map, &VonNeumann), 15);
+        assert_eq!(multiply_largest_basins(&height_map, &VonNeumann), Ok(1134));
+        assert_eq!(multiply_largest_basins(&height_map, &Moore), Ok(35));
     }
 
     #[test]
-    fn problem2_real() {
-        let content = std::fs::read_to_string(DATA_PATH).unwrap();
-        let height_map = parse_input(&content);
-        assert_eq!(problem2(height_map), 959136);
+    fn problem_edge_cases() {
+        assert_eq!(problem1(Grid::from_rows(vec![vec![4]]).unwrap()), 5);
+        assert!(matches!(
+            problem2(Grid::from_rows(vec![vec![1, 10]]).unwrap()),
+            Err(AocError::InvalidInput(_))
+        ));
     }
 }
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use aoc_common::error::AocError;
+
+use crate::day16::PacketErrorKind;
+
+/// The ways a 2021 puzzle can fail that are particular to its day. These reach callers wrapped
+/// in |AocError::Day|.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// A diagnostic reading is |bits| wide, but at most |max| bits are supported.
+    TooManyBits { bits: u32, max: u32 },
+    /// A diagnostic bit position has as many 0s as 1s, so it has no most common value.
+    AmbiguousBit { bit: u32 },
+    /// Filtering the diagnostic readings for a rating left |remaining| candidates instead of one.
+    NoUniqueRating { remaining: usize },
+    /// A bingo board cannot be played.
+    InvalidBoard { board: usize, reason: String },
+    /// No bingo board wins with the numbers drawn.
+    NoWinner,
+    /// The signal patterns of a display do not map onto the ten digits.
+    UndecodableDisplay { signal: String },
+    /// A pair of polymer elements has no insertion rule.
+    MissingRule { pair: [char; 2] },
+    /// A BITS transmission does not decode to a valid packet.
+    MalformedPacket {
+        bit_position: usize,
+        kind: PacketErrorKind,
+    },
+}
+
+impl PuzzleError {
+    /// Wraps the error as the failure of |day|'s puzzle.
+    pub fn on_day(self, day: u8) -> AocError {
+        AocError::Day {
+            day,
+            source: Box::new(self),
+        }
+    }
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PuzzleError::TooManyBits { bits, max } => write!(
+                f,
+                "reading is {} bits wide, but at most {} are supported",
+                bits, max
+            ),
+            PuzzleError::AmbiguousBit { bit } => write!(f, "bit {} has as many 0s as 1s", bit),
+            PuzzleError::NoUniqueRating { remaining } => {
+                write!(
+                    f,
+                    "{} readings remain after filtering for a rating",
+                    remaining
+                )
+            }
+            PuzzleError::InvalidBoard { board, reason } => write!(f, "board {}: {}", board, reason),
+            PuzzleError::NoWinner => write!(f, "no board wins with the numbers drawn"),
+            PuzzleError::UndecodableDisplay { signal } => {
+                write!(
+                    f,
+                    "signal patterns {:?} do not decode to ten digits",
+                    signal
+                )
+            }
+            PuzzleError::MissingRule { pair } => {
+                write!(f, "no insertion rule for pair {}{}", pair[0], pair[1])
+            }
+            PuzzleError::MalformedPacket { bit_position, kind } => {
+                write!(f, "malformed packet at bit {}: {}", bit_position, kind)
+            }
+        }
+    }
+}
+
+impl Error for PuzzleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day16::PacketField;
+
+    #[test]
+    fn display() {
+        let err = PuzzleError::MalformedPacket {
+            bit_position: 142,
+            kind: PacketErrorKind::Truncated(PacketField::SubpacketCount),
+        };
+        assert_eq!(
+            err.to_string(),
+            "malformed packet at bit 142: truncated subpacket count"
+        );
+        let err = PuzzleError::MalformedPacket {
+            bit_position: 7,
+            kind: PacketErrorKind::WrongArity {
+                operator: "Minimum",
+                expected: "at least 1",
+                found: 0,
+            },
+        };
+        assert_eq!(
+            err.to_string(),
+            "malformed packet at bit 7: Minimum requires at least 1 subpackets, found 0"
+        );
+        assert_eq!(
+            PuzzleError::MissingRule { pair: ['C', 'H'] }.to_string(),
+            "no insertion rule for pair CH"
+        );
+    }
+
+    #[test]
+    fn on_day() {
+        let err = PuzzleError::NoWinner.on_day(4);
+        assert!(matches!(err, AocError::Day { day: 4, .. }));
+        assert_eq!(err.to_string(), "no board wins with the numbers drawn");
+        assert_eq!(err.day_error(), Some(&PuzzleError::NoWinner));
+    }
+}
@@ -13,5 +13,49 @@ pub mod day12;
 pub mod day13;
 pub mod day14;
 pub mod day15;
-pub mod coord;
 pub mod day16;
+pub mod error;
+
+use aoc_common::solution::Day;
+
+pub const YEAR: u16 = 2021;
+
+/// Every implemented day for this year, in order.
+pub const DAYS: &[Day] = &[
+    Day::new::<day1::Day1>(YEAR, 1),
+    Day::new::<day2::Day2>(YEAR, 2),
+    Day::new::<day3::Day3>(YEAR, 3),
+    Day::new::<day4::Day4>(YEAR, 4),
+    Day::new::<day5::Day5>(YEAR, 5),
+    Day::new::<day6::Day6>(YEAR, 6),
+    Day::new::<day7::Day7>(YEAR, 7),
+    Day::new::<day8::Day8>(YEAR, 8),
+    Day::new::<day9::Day9>(YEAR, 9),
+    Day::new::<day10::Day10>(YEAR, 10),
+    Day::new::<day11::Day11>(YEAR, 11),
+    Day::new::<day12::Day12>(YEAR, 12),
+    Day::new::<day13::Day13>(YEAR, 13),
+    Day::new::<day14::Day14>(YEAR, 14),
+    Day::new::<day15::Day15>(YEAR, 15),
+    Day::new::<day16::Day16>(YEAR, 16),
+];
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::answers::verify;
+    use aoc_common::input::DataDir;
+
+    use super::*;
+
+    #[test]
+    fn known_answers() {
+        let data_dir = DataDir::from_env_or_crate(env!("CARGO_MANIFEST_DIR"));
+        let checks = verify(DAYS, &data_dir).unwrap();
+        let failures: Vec<String> = checks
+            .iter()
+            .filter(|check| check.outcome.is_failure())
+            .map(|check| check.to_string())
+            .collect();
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
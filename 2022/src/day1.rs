@@ -1,33 +1,59 @@
-pub fn calories_per_elf(input: &str) -> Vec<u32> {
+use aoc_common::error::AocError;
+use aoc_common::parse::{lines, ParseError};
+use aoc_common::solution::Solution;
+
+pub fn calories_per_elf(input: &str) -> Result<Vec<u32>, ParseError> {
     let mut calories_per_elf = vec![0u32];
-    for line in input.split('\n') {
-        if line.len() == 0 {
+    for line in lines(input) {
+        let text = line.text.trim();
+        if text.is_empty() {
             calories_per_elf.push(0);
         } else {
             let elf_index = calories_per_elf.len() - 1;
-            calories_per_elf[elf_index] += line.parse::<u32>().unwrap();
+            calories_per_elf[elf_index] += line.parse_number::<u32>(text)?;
         }
     }
 
-    calories_per_elf
+    Ok(calories_per_elf)
 }
 
-pub fn problem1(input: &str) -> u32 {
-    *calories_per_elf(input).iter().max().unwrap()
+pub fn problem1(calories: &[u32]) -> Result<u32, AocError> {
+    calories.iter().max().copied().ok_or(AocError::EmptyInput)
 }
 
-pub fn problem2(input: &str) -> u32 {
-    let mut calories = calories_per_elf(input);
+pub fn problem2(calories: &[u32]) -> u32 {
+    let mut calories = calories.to_vec();
     calories.sort_unstable();
     calories.iter().rev().take(3).sum::<u32>()
 }
 
+pub struct Day1 {
+    calories: Vec<u32>,
+}
+
+impl Solution for Day1 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day1 {
+            calories: calories_per_elf(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<u32, AocError> {
+        problem1(&self.calories)
+    }
+
+    fn part2(&self) -> Result<u32, AocError> {
+        Ok(problem2(&self.calories))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const DATA_PATH: &'static str = "data/day1";
-
     #[test]
     fn problem1_example() {
         let example = "\
@@ -46,18 +72,12 @@ mod tests {
 
 10000";
 
-        assert_eq!(problem1(&example), 24000);
-    }
-
-    #[test]
-    fn problem1_real() {
-        let data = std::fs::read_to_string(DATA_PATH).unwrap();
-        assert_eq!(problem1(&data), 70509);
+        assert_eq!(problem1(&calories_per_elf(example).unwrap()), Ok(24000));
     }
 
     #[test]
-    fn problem2_real() {
-        let data = std::fs::read_to_string(DATA_PATH).unwrap();
-        assert_eq!(problem2(&data), 208567);
+    fn parse_error() {
+        let err = calories_per_elf("1000\n2000\n\n40o0\n").unwrap_err();
+        assert_eq!((err.line, err.column), (4, 1));
     }
 }
\ No newline at end of file
@@ -1,41 +1,95 @@
-pub fn solve(input: &str, table: &[u32; 9]) -> u32 {
-    input
-        .lines()
-        .map(|line| line.chars())
-        .fold(0u32, |score, mut chars| {
-            let row_offset = (chars.nth(0).unwrap() as u8 - 'A' as u8) * 3;
-            // nth() consumes characters, so even though its index 2 in the line, its index 1 after consuming
-            // the 0th character on the previous line
-            let col_offset = chars.nth(1).unwrap() as u8 - 'X' as u8;
-            score + table[(row_offset + col_offset) as usize]
-        })
+use std::str::FromStr;
+
+use aoc_common::error::AocError;
+use aoc_common::parse::{non_empty_lines, Line, ParseError, ParseErrorKind};
+use aoc_common::solution::Solution;
+
+const ROCK: u32 = 1;
+const PAPER: u32 = 2;
+const SCISSORS: u32 = 3;
+
+const WIN: u32 = 6;
+const DRAW: u32 = 3;
+const LOSE: u32 = 0;
+
+pub const PROBLEM1_LOOKUP: [u32; 9] = [
+    ROCK + DRAW, PAPER + WIN,  SCISSORS + LOSE,
+    ROCK + LOSE, PAPER + DRAW, SCISSORS + WIN,
+    ROCK + WIN,  PAPER + LOSE, SCISSORS + DRAW,
+];
+
+pub const PROBLEM2_LOOKUP: [u32; 9] = [
+    LOSE + SCISSORS, DRAW + ROCK,     WIN + PAPER,
+    LOSE + ROCK,     DRAW + PAPER,    WIN + SCISSORS,
+    LOSE + PAPER,    DRAW + SCISSORS, WIN + ROCK,
+];
+
+/// A single round of the strategy guide. The first column is the opponent's choice and picks
+/// the lookup table row; the second column picks the lookup table column.
+#[derive(Clone, Copy, Debug)]
+pub struct Round {
+    row: u8,
+    col: u8,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl FromStr for Round {
+    type Err = ParseError;
 
-    const DATA_PATH: &'static str = "data/day2";
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line { number: 1, text: s };
+        let (opponent, response) = line.split_once(" ", "two columns")?;
+        let row = match opponent {
+            "A" | "B" | "C" => opponent.as_bytes()[0] - b'A',
+            _ => return Err(line.error_at(opponent, ParseErrorKind::Expected("A, B or C"))),
+        };
+        let col = match response {
+            "X" | "Y" | "Z" => response.as_bytes()[0] - b'X',
+            _ => return Err(line.error_at(response, ParseErrorKind::Expected("X, Y or Z"))),
+        };
+
+        Ok(Round { row, col })
+    }
+}
 
-    const ROCK: u32 = 1;
-    const PAPER: u32 = 2;
-    const SCISSORS: u32 = 3;
+pub fn parse_input(input: &str) -> Result<Vec<Round>, ParseError> {
+    non_empty_lines(input)
+        .map(|line| line.parse(line.text.trim()))
+        .collect()
+}
 
-    const WIN: u32 = 6;
-    const DRAW: u32 = 3;
-    const LOSE: u32 = 0;
+pub fn solve(rounds: &[Round], table: &[u32; 9]) -> u32 {
+    rounds
+        .iter()
+        .map(|round| table[(round.row * 3 + round.col) as usize])
+        .sum()
+}
 
-    const PROBLEM1_LOOKUP: [u32; 9] = [
-        ROCK + DRAW, PAPER + WIN,  SCISSORS + LOSE,
-        ROCK + LOSE, PAPER + DRAW, SCISSORS + WIN,
-        ROCK + WIN,  PAPER + LOSE, SCISSORS + DRAW,
-    ];
+pub struct Day2 {
+    rounds: Vec<Round>,
+}
 
-    const PROBLEM2_LOOKUP: [u32; 9] = [
-        LOSE + SCISSORS, DRAW + ROCK,     WIN + PAPER,
-        LOSE + ROCK,     DRAW + PAPER,    WIN + SCISSORS,
-        LOSE + PAPER,    DRAW + SCISSORS, WIN + ROCK,
-    ];
+impl Solution for Day2 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, AocError> {
+        Ok(Day2 {
+            rounds: parse_input(input)?,
+        })
+    }
+
+    fn part1(&self) -> Result<u32, AocError> {
+        Ok(solve(&self.rounds, &PROBLEM1_LOOKUP))
+    }
+
+    fn part2(&self) -> Result<u32, AocError> {
+        Ok(solve(&self.rounds, &PROBLEM2_LOOKUP))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn problem1_example() {
@@ -44,13 +98,13 @@ A Y
 B X
 C Z";
 
-        assert_eq!(solve(&example, &PROBLEM1_LOOKUP), 15);
+        assert_eq!(solve(&parse_input(example).unwrap(), &PROBLEM1_LOOKUP), 15);
     }
 
     #[test]
-    fn problem1_real() {
-        let data = std::fs::read_to_string(DATA_PATH).unwrap();
-        assert_eq!(solve(&data, &PROBLEM1_LOOKUP), 11603);
+    fn parse_error() {
+        let err = parse_input("A Y\nB W\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
     }
 
     #[test]
@@ -60,12 +114,6 @@ A Y
 B X
 C Z";
 
-        assert_eq!(solve(&example, &PROBLEM2_LOOKUP), 12);
-    }
-
-    #[test]
-    fn problem2_real() {
-        let data = std::fs::read_to_string(DATA_PATH).unwrap();
-        assert_eq!(solve(&data, &PROBLEM2_LOOKUP), 12725);
+        assert_eq!(solve(&parse_input(example).unwrap(), &PROBLEM2_LOOKUP), 12);
     }
 }
\ No newline at end of file
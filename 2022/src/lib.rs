@@ -1,2 +1,32 @@
 pub mod day1;
 pub mod day2;
+
+use aoc_common::solution::Day;
+
+pub const YEAR: u16 = 2022;
+
+/// Every implemented day for this year, in order.
+pub const DAYS: &[Day] = &[
+    Day::new::<day1::Day1>(YEAR, 1),
+    Day::new::<day2::Day2>(YEAR, 2),
+];
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::answers::verify;
+    use aoc_common::input::DataDir;
+
+    use super::*;
+
+    #[test]
+    fn known_answers() {
+        let data_dir = DataDir::from_env_or_crate(env!("CARGO_MANIFEST_DIR"));
+        let checks = verify(DAYS, &data_dir).unwrap();
+        let failures: Vec<String> = checks
+            .iter()
+            .filter(|check| check.outcome.is_failure())
+            .map(|check| check.to_string())
+            .collect();
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
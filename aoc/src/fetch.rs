@@ -0,0 +1,255 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use aoc_common::error::AocError;
+use aoc_common::input::DataDir;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// The shortest time allowed between two downloads, to go easy on the server.
+pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+const USER_AGENT: &str = concat!("aoc-runner/", env!("CARGO_PKG_VERSION"));
+
+/// Whether |Fetcher::fetch| found the input in the cache or had to download it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+/// Downloads puzzle inputs into a data directory. An input is only downloaded if it isn't
+/// already there, and downloads are spaced at least |min_interval| apart.
+pub struct Fetcher {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    data_dir: DataDir,
+    min_interval: Duration,
+    last_request: Option<Instant>,
+}
+
+impl Fetcher {
+    pub fn new(session: &str, data_dir: DataDir) -> Self {
+        Fetcher {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .build(),
+            base_url: DEFAULT_BASE_URL.to_owned(),
+            session: session.trim().to_owned(),
+            data_dir,
+            min_interval: DEFAULT_MIN_INTERVAL,
+            last_request: None,
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_owned();
+        self
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Returns the path of the input for |year| and |day|, downloading it first if it isn't
+    /// cached.
+    pub fn fetch(&mut self, year: u16, day: u8) -> Result<Fetched, AocError> {
+        let path = self.data_dir.input_path(year, day);
+        if path.exists() {
+            return Ok(Fetched::Cached(path));
+        }
+
+        let input = self.download(year, day)?;
+        write_atomically(&path, &input)?;
+        Ok(Fetched::Downloaded(path))
+    }
+
+    fn download(&mut self, year: u16, day: u8) -> Result<String, AocError> {
+        let error = |reason: String| AocError::Fetch { year, day, reason };
+
+        if let Some(last_request) = self.last_request {
+            let elapsed = last_request.elapsed();
+            if elapsed < self.min_interval {
+                thread::sleep(self.min_interval - elapsed);
+            }
+        }
+        self.last_request = Some(Instant::now());
+
+        let url = format!("{}/{}/day/{}/input", self.base_url, year, day);
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", USER_AGENT)
+            .call()
+            .map_err(|err| match err {
+                ureq::Error::Status(status, _) => error(format!("server responded {}", status)),
+                ureq::Error::Transport(transport) => error(transport.to_string()),
+            })?;
+
+        let input = response
+            .into_string()
+            .map_err(|err| error(err.to_string()))?;
+        if input.is_empty() {
+            return Err(error("server returned an empty input".to_owned()));
+        }
+        Ok(input)
+    }
+}
+
+/// Writes through a temporary file, so that an interrupted download never leaves a partial
+/// input in the cache.
+fn write_atomically(path: &Path, contents: &str) -> Result<(), AocError> {
+    let io_error = |path: &Path, err: std::io::Error| AocError::Io {
+        path: path.display().to_string(),
+        reason: err.to_string(),
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| io_error(dir, err))?;
+    }
+    let partial = path.with_extension("partial");
+    fs::write(&partial, contents).map_err(|err| io_error(&partial, err))?;
+    fs::rename(&partial, path).map_err(|err| io_error(path, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::{self, Receiver};
+
+    use super::*;
+
+    /// A request seen by the stand-in server.
+    struct Request {
+        path: String,
+        cookie: Option<String>,
+    }
+
+    /// Serves |responses| in order, one per connection, on a local port. Returns the base URL
+    /// and the requests as they arrive.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut cookie = None;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let header = header.trim_end();
+                    if header.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(": ") {
+                        if name.eq_ignore_ascii_case("cookie") {
+                            cookie = Some(value.to_owned());
+                        }
+                    }
+                }
+
+                let path = request_line.split(' ').nth(1).unwrap_or("").to_owned();
+                sender.send(Request { path, cookie }).unwrap();
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Stand-in\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (base_url, receiver)
+    }
+
+    fn temp_data_dir(name: &str) -> (PathBuf, DataDir) {
+        let root = std::env::temp_dir().join(format!("aoc-fetch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        (root.clone(), DataDir::Custom(root))
+    }
+
+    #[test]
+    fn downloads_once_then_uses_cache() {
+        let (base_url, requests) = serve(vec![(200, "1\n2\n3\n")]);
+        let (root, data_dir) = temp_data_dir("cache");
+        let mut fetcher = Fetcher::new("abc123\n", data_dir.clone()).with_base_url(&base_url);
+
+        let path = data_dir.input_path(2021, 1);
+        assert_eq!(
+            fetcher.fetch(2021, 1),
+            Ok(Fetched::Downloaded(path.clone()))
+        );
+        assert_eq!(fetcher.fetch(2021, 1), Ok(Fetched::Cached(path.clone())));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1\n2\n3\n");
+
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/2021/day/1/input");
+        assert_eq!(request.cookie.as_deref(), Some("session=abc123"));
+        assert!(requests.try_recv().is_err());
+
+        // a fresh fetcher sees the cached file too
+        let mut fetcher = Fetcher::new("abc123", data_dir).with_base_url(&base_url);
+        assert_eq!(fetcher.fetch(2021, 1), Ok(Fetched::Cached(path)));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn error_status_is_not_cached() {
+        let (base_url, requests) = serve(vec![(404, "Not yet!"), (200, "42\n")]);
+        let (root, data_dir) = temp_data_dir("error");
+        let mut fetcher = Fetcher::new("abc123", data_dir.clone())
+            .with_base_url(&base_url)
+            .with_min_interval(Duration::ZERO);
+
+        assert_eq!(
+            fetcher.fetch(2021, 25),
+            Err(AocError::Fetch {
+                year: 2021,
+                day: 25,
+                reason: "server responded 404".to_owned()
+            })
+        );
+        assert!(!data_dir.input_path(2021, 25).exists());
+
+        assert!(matches!(
+            fetcher.fetch(2021, 25),
+            Ok(Fetched::Downloaded(_))
+        ));
+        assert_eq!(requests.iter().count(), 2);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn requests_are_spaced_out() {
+        let interval = Duration::from_millis(300);
+        let (base_url, requests) = serve(vec![(200, "a\n"), (200, "b\n")]);
+        let (root, data_dir) = temp_data_dir("throttle");
+        let mut fetcher = Fetcher::new("abc123", data_dir)
+            .with_base_url(&base_url)
+            .with_min_interval(interval);
+
+        let start = Instant::now();
+        fetcher.fetch(2022, 1).unwrap();
+        fetcher.fetch(2022, 2).unwrap();
+        assert!(start.elapsed() >= interval);
+
+        let paths: Vec<String> = requests.iter().map(|request| request.path).collect();
+        assert_eq!(paths, ["/2022/day/1/input", "/2022/day/2/input"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use aoc_common::answers::{verify, Outcome};
+use aoc_common::input::{DataDir, DATA_DIR_ENV};
+use aoc_common::solution::{find_day, Day};
+use clap::{Parser, Subcommand};
+
+use crate::fetch::{Fetched, Fetcher, DEFAULT_BASE_URL, DEFAULT_MIN_INTERVAL};
+
+mod fetch;
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Runs Advent of Code solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Directory of puzzle inputs and answers, with a subdirectory per year. Defaults to the
+    /// `data/` directory of each year's crate, in the checkout that holds the current directory
+    /// or this executable
+    #[arg(long, global = true, env = DATA_DIR_ENV)]
+    data_dir: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve one or both parts of a single day's puzzle
+    Run {
+        /// The puzzle year, e.g. 2021
+        year: u16,
+
+        /// The puzzle day, from 1 to 25
+        day: u8,
+
+        /// The part to solve. Both parts are solved if omitted
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// The puzzle input file, or `-` to read from stdin. Input is read from the data
+        /// directory if omitted
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Check every implemented day against the known answers in the data directory
+    Verify {
+        /// Only verify this year. Every year is verified if omitted
+        year: Option<u16>,
+    },
+    /// Download puzzle inputs into the data directory, skipping any that are already there
+    Fetch {
+        /// The puzzle year, e.g. 2021
+        year: u16,
+
+        /// The puzzle day. Every implemented day of the year is downloaded if omitted
+        day: Option<u8>,
+
+        /// The session cookie of a logged in adventofcode.com account
+        #[arg(long, env = "AOC_SESSION", hide_env_values = true)]
+        session: String,
+
+        /// The minimum number of seconds between two downloads
+        #[arg(long, default_value_t = DEFAULT_MIN_INTERVAL.as_secs())]
+        min_interval: u64,
+
+        /// The server to download from
+        #[arg(long, default_value = DEFAULT_BASE_URL, hide = true)]
+        base_url: String,
+    },
+}
+
+fn read_input(
+    data_dir: &DataDir,
+    year: u16,
+    day: u8,
+    path: Option<&Path>,
+) -> Result<String, String> {
+    let read_error = |err: io::Error| format!("failed to read input: {}", err);
+    match path {
+        None => data_dir
+            .read_input(year, day)
+            .map_err(|err| err.to_string()),
+        Some(path) if path == Path::new("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(read_error)?;
+            Ok(input)
+        }
+        Some(path) => fs::read_to_string(path).map_err(read_error),
+    }
+}
+
+const YEARS: &[u16] = &[aoc2021::YEAR, aoc2022::YEAR];
+
+fn registry(year: u16) -> Option<&'static [Day]> {
+    match year {
+        aoc2021::YEAR => Some(aoc2021::DAYS),
+        aoc2022::YEAR => Some(aoc2022::DAYS),
+        _ => None,
+    }
+}
+
+fn run(
+    data_dir: &DataDir,
+    year: u16,
+    day: u8,
+    part: Option<u8>,
+    input: Option<&Path>,
+) -> Result<(), String> {
+    let entry = registry(year)
+        .and_then(|days| find_day(days, day))
+        .ok_or_else(|| format!("no solution for {} day {}", year, day))?;
+    let input = read_input(data_dir, year, day, input)?;
+    let solution = entry
+        .parse(&input)
+        .map_err(|err| format!("failed to parse input: {}", err))?;
+
+    let part1 = || {
+        solution
+            .part1()
+            .map_err(|err| format!("failed to solve part 1: {}", err))
+    };
+    let part2 = || {
+        solution
+            .part2()
+            .map_err(|err| format!("failed to solve part 2: {}", err))
+    };
+    match part {
+        Some(1) => println!("{}", part1()?),
+        Some(_) => println!("{}", part2()?),
+        None => {
+            println!("Part 1: {}", part1()?);
+            println!("Part 2: {}", part2()?);
+        }
+    }
+
+    Ok(())
+}
+
+fn verify_years(data_dir: &DataDir, year: Option<u16>) -> Result<(), String> {
+    let years = match year {
+        Some(year) => vec![year],
+        None => YEARS.to_vec(),
+    };
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for year in years {
+        let days = registry(year).ok_or_else(|| format!("no solutions for {}", year))?;
+        for check in verify(days, data_dir).map_err(|err| err.to_string())? {
+            println!("{}", check);
+            match check.outcome {
+                Outcome::Pass => passed += 1,
+                Outcome::MissingAnswer | Outcome::MissingInput => missing += 1,
+                _ => failed += 1,
+            }
+        }
+    }
+
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    match failed {
+        0 => Ok(()),
+        _ => Err("some answers did not match".to_owned()),
+    }
+}
+
+fn fetch_inputs(fetcher: &mut Fetcher, year: u16, day: Option<u8>) -> Result<(), String> {
+    let days: Vec<u8> = match day {
+        Some(day) => vec![day],
+        None => registry(year)
+            .ok_or_else(|| format!("no solutions for {}", year))?
+            .iter()
+            .map(|entry| entry.day)
+            .collect(),
+    };
+
+    for day in days {
+        match fetcher.fetch(year, day).map_err(|err| err.to_string())? {
+            Fetched::Cached(path) => {
+                println!("{} day {:>2}: cached at {}", year, day, path.display())
+            }
+            Fetched::Downloaded(path) => {
+                println!("{} day {:>2}: downloaded to {}", year, day, path.display())
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let data_dir = match cli.data_dir {
+        Some(dir) => DataDir::Custom(dir),
+        None => DataDir::Default,
+    };
+    let result = match cli.command {
+        Command::Run {
+            year,
+            day,
+            part,
+            input,
+        } => run(&data_dir, year, day, part, input.as_deref()),
+        Command::Verify { year } => verify_years(&data_dir, year),
+        Command::Fetch {
+            year,
+            day,
+            session,
+            min_interval,
+            base_url,
+        } => {
+            let mut fetcher = Fetcher::new(&session, data_dir)
+                .with_base_url(&base_url)
+                .with_min_interval(Duration::from_secs(min_interval));
+            fetch_inputs(&mut fetcher, year, day)
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use toml::{Table, Value};
+
+use crate::error::AocError;
+use crate::input::DataDir;
+use crate::solution::Day;
+
+/// The name of the answers file inside a data directory.
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// Known answers for a set of puzzle inputs, keyed by year, day and part. Each day is a table,
+/// and answers may be written as integers or strings:
+///
+/// ```toml
+/// [2021.day1]
+/// part1 = 1532
+/// part2 = "1571"
+/// ```
+#[derive(Debug, Default)]
+pub struct Answers {
+    answers: HashMap<(u16, u8, u8), String>,
+}
+
+fn expect_table(value: Value, what: &str) -> Result<Table, AocError> {
+    match value {
+        Value::Table(table) => Ok(table),
+        _ => Err(AocError::InvalidAnswers(format!(
+            "expected a table for {}",
+            what
+        ))),
+    }
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self, AocError> {
+        let invalid = AocError::InvalidAnswers;
+        let years: Table = text
+            .parse()
+            .map_err(|err: toml::de::Error| invalid(err.message().to_owned()))?;
+
+        let mut answers = HashMap::new();
+        for (year_key, days) in years {
+            let year = year_key
+                .parse::<u16>()
+                .map_err(|_| invalid(format!("{:?} is not a year", year_key)))?;
+            for (day_key, parts) in expect_table(days, &year_key)? {
+                let day = day_key
+                    .strip_prefix("day")
+                    .and_then(|day| day.parse::<u8>().ok())
+                    .ok_or_else(|| invalid(format!("{:?} is not a day", day_key)))?;
+                for (part_key, answer) in expect_table(parts, &day_key)? {
+                    let part = match part_key.as_str() {
+                        "part1" => 1,
+                        "part2" => 2,
+                        _ => return Err(invalid(format!("{:?} is not a part", part_key))),
+                    };
+                    let answer = match answer {
+                        Value::String(answer) => answer,
+                        Value::Integer(answer) => answer.to_string(),
+                        _ => {
+                            return Err(invalid(format!(
+                                "the answer for {} {} {} is not an integer or string",
+                                year, day_key, part_key
+                            )))
+                        }
+                    };
+                    answers.insert((year, day, part), answer);
+                }
+            }
+        }
+
+        Ok(Answers { answers })
+    }
+
+    /// Reads the answers at |path|. A missing file has no answers.
+    pub fn read(path: &Path) -> Result<Self, AocError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Answers::parse(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(err) => Err(AocError::Io {
+                path: path.display().to_string(),
+                reason: err.to_string(),
+            }),
+        }
+    }
+
+    pub fn get(&self, year: u16, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(year, day, part)).map(String::as_str)
+    }
+}
+
+/// How one part of one day compares to its known answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// The input could not be parsed, or the part could not be solved.
+    Error(AocError),
+    MissingAnswer,
+    MissingInput,
+}
+
+impl Outcome {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Fail { .. } | Outcome::Error(_))
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail { expected, actual } => {
+                write!(f, "fail: expected {:?}, got {:?}", expected, actual)
+            }
+            Outcome::Error(err) => write!(f, "error: {}", err),
+            Outcome::MissingAnswer => write!(f, "missing answer"),
+            Outcome::MissingInput => write!(f, "missing input"),
+        }
+    }
+}
+
+/// The outcome of verifying a single part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    pub outcome: Outcome,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} day {:>2} part {}: {}",
+            self.year, self.day, self.part, self.outcome
+        )
+    }
+}
+
+/// Solves both parts of |day| from |input| and compares them to |answers|. Parts without a
+/// known answer are not solved.
+pub fn verify_day(day: &Day, input: Option<&str>, answers: &Answers) -> Vec<Check> {
+    let expected = [1, 2].map(|part| answers.get(day.year, day.day, part));
+    let check = |part: u8, outcome: Outcome| Check {
+        year: day.year,
+        day: day.day,
+        part,
+        outcome,
+    };
+
+    let input = match input {
+        Some(input) => input,
+        None => {
+            return vec![
+                check(1, Outcome::MissingInput),
+                check(2, Outcome::MissingInput),
+            ]
+        }
+    };
+    if expected.iter().all(Option::is_none) {
+        return vec![
+            check(1, Outcome::MissingAnswer),
+            check(2, Outcome::MissingAnswer),
+        ];
+    }
+
+    let solution = match day.parse(input) {
+        Ok(solution) => solution,
+        Err(err) => {
+            return vec![
+                check(1, Outcome::Error(err.clone())),
+                check(2, Outcome::Error(err)),
+            ]
+        }
+    };
+
+    [1, 2]
+        .into_iter()
+        .zip(expected)
+        .map(|(part, expected)| {
+            let expected = match expected {
+                Some(expected) => expected,
+                None => return check(part, Outcome::MissingAnswer),
+            };
+            let actual = match part {
+                1 => solution.part1(),
+                _ => solution.part2(),
+            };
+            check(
+                part,
+                match actual {
+                    Ok(actual) if actual == expected => Outcome::Pass,
+                    Ok(actual) => Outcome::Fail {
+                        expected: expected.to_owned(),
+                        actual,
+                    },
+                    Err(err) => Outcome::Error(err),
+                },
+            )
+        })
+        .collect()
+}
+
+/// Verifies every day in |days| against its input and the answers file for its year in
+/// |data_dir|.
+pub fn verify(days: &[Day], data_dir: &DataDir) -> Result<Vec<Check>, AocError> {
+    let mut answers_per_year = HashMap::new();
+
+    let mut checks = Vec::new();
+    for day in days {
+        let answers = match answers_per_year.entry(day.year) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Answers::read(&data_dir.answers_path(day.year))?),
+        };
+        let input = match data_dir.read_input(day.year, day.day) {
+            Ok(input) => Some(input),
+            Err(AocError::InputNotFound { .. }) => None,
+            Err(err) => return Err(err),
+        };
+        checks.extend(verify_day(day, input.as_deref(), answers));
+    }
+
+    Ok(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::ParseError;
+    use crate::solution::Solution;
+
+    struct Echo(String);
+
+    impl Solution for Echo {
+        type Answer1 = String;
+        type Answer2 = usize;
+
+        fn parse(input: &str) -> Result<Self, AocError> {
+            match input.trim() {
+                "" => Err(ParseError::end_of(input).into()),
+                text => Ok(Echo(text.to_owned())),
+            }
+        }
+
+        fn part1(&self) -> Result<String, AocError> {
+            Ok(self.0.clone())
+        }
+
+        fn part2(&self) -> Result<usize, AocError> {
+            Ok(self.0.len())
+        }
+    }
+
+    const ECHO: Day = Day::new::<Echo>(2000, 1);
+
+    #[test]
+    fn parse() {
+        let answers = Answers::parse(
+            r#"
+[2021.day1]
+part1 = 1532
+part2 = "1571"
+
+[2021.day13]
+part2 = """
+X  X
+XXXX"""
+
+[2022]
+day2 = { part1 = 11603 }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(answers.get(2021, 1, 1), Some("1532"));
+        assert_eq!(answers.get(2021, 1, 2), Some("1571"));
+        assert_eq!(answers.get(2021, 13, 1), None);
+        assert_eq!(answers.get(2021, 13, 2), Some("X  X\nXXXX"));
+        assert_eq!(answers.get(2022, 2, 1), Some("11603"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for text in [
+            "[2021.day1]\npart1 = ",
+            "[twenty.day1]\npart1 = 1",
+            "[2021.one]\npart1 = 1",
+            "[2021.day1]\npart3 = 1",
+            "[2021.day1]\npart1 = 1.5",
+            "2021 = 5",
+        ] {
+            assert!(
+                matches!(Answers::parse(text), Err(AocError::InvalidAnswers(_))),
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn verify_outcomes() {
+        let answers = Answers::parse("[2000.day1]\npart1 = \"abc\"\npart2 = 4\n").unwrap();
+        let outcomes = |input| {
+            verify_day(&ECHO, input, &answers)
+                .into_iter()
+                .map(|check| check.outcome)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            outcomes(Some("abc\n")),
+            [
+                Outcome::Pass,
+                Outcome::Fail {
+                    expected: "4".to_owned(),
+                    actual: "3".to_owned()
+                }
+            ]
+        );
+        assert_eq!(
+            outcomes(None),
+            [Outcome::MissingInput, Outcome::MissingInput]
+        );
+        assert!(outcomes(Some("\n")).iter().all(Outcome::is_failure));
+
+        let none = Answers::default();
+        let checks = verify_day(&ECHO, Some("abc"), &none);
+        assert_eq!(checks[0].outcome, Outcome::MissingAnswer);
+        assert_eq!(checks[1].to_string(), "2000 day  1 part 2: missing answer");
+    }
+
+    #[test]
+    fn verify_directory() {
+        let root = std::env::temp_dir().join(format!("aoc-answers-{}", std::process::id()));
+        let data_dir = DataDir::Custom(root.clone());
+        fs::create_dir_all(data_dir.year_dir(2000)).unwrap();
+        fs::write(data_dir.input_path(2000, 1), "abcd\n").unwrap();
+        fs::write(
+            data_dir.answers_path(2000),
+            "[2000.day1]\npart1 = \"abcd\"\n",
+        )
+        .unwrap();
+
+        let checks = verify(&[ECHO, Day::new::<Echo>(2000, 2)], &data_dir);
+        fs::remove_dir_all(&root).unwrap();
+
+        let outcomes: Vec<_> = checks.unwrap().into_iter().map(|c| c.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                Outcome::Pass,
+                Outcome::MissingAnswer,
+                Outcome::MissingInput,
+                Outcome::MissingInput
+            ]
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use std::hint::black_box;
+
+use criterion::Criterion;
+
+use crate::input::DataDir;
+use crate::solution::Day;
+
+/// Benches parsing and each part separately for every day in |days|, using the real inputs
+/// from |data_dir|. Days without an input are skipped.
+pub fn bench_days(c: &mut Criterion, days: &[Day], data_dir: &DataDir) {
+    for day in days {
+        let input = match data_dir.read_input(day.year, day.day) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("skipping: {}", err);
+                continue;
+            }
+        };
+        let solution = day.parse(&input).unwrap();
+
+        let mut group = c.benchmark_group(format!("{}/day{:02}", day.year, day.day));
+        group.bench_function("parse", |b| b.iter(|| day.parse(black_box(&input))));
+        group.bench_function("part1", |b| b.iter(|| solution.part1()));
+        group.bench_function("part2", |b| b.iter(|| solution.part2()));
+        group.finish();
+    }
+}
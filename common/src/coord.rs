@@ -0,0 +1,789 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A position on a grid, counted from the top left. Rows grow downwards and columns grow to
+/// the right. Coordinates are signed, so a |Coord| can also be an offset between two positions
+/// or a position off the edge of a grid.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Coord {
+    pub row: i32,
+    pub col: i32,
+}
+
+impl Coord {
+    pub const ORIGIN: Coord = Coord { row: 0, col: 0 };
+
+    pub const fn new(row: i32, col: i32) -> Self {
+        Coord { row, col }
+    }
+
+    /// For puzzles that write positions as `x,y`, where |x| is the column and |y| the row.
+    pub const fn from_xy(x: i32, y: i32) -> Self {
+        Coord { row: y, col: x }
+    }
+
+    pub const fn x(&self) -> i32 {
+        self.col
+    }
+
+    pub const fn y(&self) -> i32 {
+        self.row
+    }
+
+    /// The position at |row| and |col| of a grid.
+    ///
+    /// Panics if either index does not fit in an |i32|.
+    pub fn from_indices(row: usize, col: usize) -> Self {
+        Coord {
+            row: row.try_into().expect("row index fits in an i32"),
+            col: col.try_into().expect("column index fits in an i32"),
+        }
+    }
+
+    /// The row and column indices of this position, or |None| if it is above or to the left of
+    /// the grid.
+    pub fn to_indices(&self) -> Option<(usize, usize)> {
+        Some((self.row.try_into().ok()?, self.col.try_into().ok()?))
+    }
+
+    /// The number of orthogonal steps to |other|.
+    pub fn manhattan_distance(&self, other: &Coord) -> u32 {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    /// The number of steps to |other| when diagonal steps are allowed.
+    pub fn chebyshev_distance(&self, other: &Coord) -> u32 {
+        self.row
+            .abs_diff(other.row)
+            .max(self.col.abs_diff(other.col))
+    }
+
+    /// The straight-line distance to |other|.
+    pub fn distance_to(&self, other: &Coord) -> f64 {
+        let row_distance = self.row.abs_diff(other.row) as f64;
+        let col_distance = self.col.abs_diff(other.col) as f64;
+
+        (row_distance.powf(2.0) + col_distance.powf(2.0)).sqrt()
+    }
+
+    /// Each component clamped to -1, 0 or 1. For an offset along a row, column or diagonal,
+    /// this is the single step that moves in the same direction.
+    pub fn signum(&self) -> Coord {
+        Coord {
+            row: self.row.signum(),
+            col: self.col.signum(),
+        }
+    }
+
+    /// Whether |other| lies on the same row, column or diagonal.
+    pub fn is_aligned_with(&self, other: &Coord) -> bool {
+        let offset = *other - *self;
+        offset.row == 0 || offset.col == 0 || offset.row.abs() == offset.col.abs()
+    }
+
+    /// Every position from here to |end| inclusive, one step at a time. |end| must be on the
+    /// same row, column or diagonal.
+    pub fn line_to(self, end: Coord) -> impl Iterator<Item = Coord> {
+        assert!(self.is_aligned_with(&end));
+        let step = (end - self).signum();
+        (0..=self.chebyshev_distance(&end) as i32).map(move |i| self + step * i)
+    }
+
+    pub fn step(self, direction: Direction) -> Coord {
+        self + direction.offset()
+    }
+}
+
+impl From<(i32, i32)> for Coord {
+    fn from((row, col): (i32, i32)) -> Self {
+        Coord { row, col }
+    }
+}
+
+impl From<Coord> for (i32, i32) {
+    fn from(coord: Coord) -> Self {
+        (coord.row, coord.col)
+    }
+}
+
+impl Add for Coord {
+    type Output = Coord;
+
+    fn add(self, rhs: Coord) -> Coord {
+        Coord {
+            row: self.row + rhs.row,
+            col: self.col + rhs.col,
+        }
+    }
+}
+
+impl AddAssign for Coord {
+    fn add_assign(&mut self, rhs: Coord) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Coord {
+    type Output = Coord;
+
+    fn sub(self, rhs: Coord) -> Coord {
+        Coord {
+            row: self.row - rhs.row,
+            col: self.col - rhs.col,
+        }
+    }
+}
+
+impl SubAssign for Coord {
+    fn sub_assign(&mut self, rhs: Coord) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<i32> for Coord {
+    type Output = Coord;
+
+    fn mul(self, rhs: i32) -> Coord {
+        Coord {
+            row: self.row * rhs,
+            col: self.col * rhs,
+        }
+    }
+}
+
+impl Neg for Coord {
+    type Output = Coord;
+
+    fn neg(self) -> Coord {
+        Coord {
+            row: -self.row,
+            col: -self.col,
+        }
+    }
+}
+
+/// One of the four orthogonal directions on a grid.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// In clockwise order, starting from |Up|.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// The offset of a single step in this direction.
+    pub const fn offset(self) -> Coord {
+        match self {
+            Direction::Up => Coord::new(-1, 0),
+            Direction::Right => Coord::new(0, 1),
+            Direction::Down => Coord::new(1, 0),
+            Direction::Left => Coord::new(0, -1),
+        }
+    }
+
+    pub const fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub const fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Right => Direction::Up,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+        }
+    }
+
+    pub const fn opposite(self) -> Direction {
+        self.turn_right().turn_right()
+    }
+}
+
+/// The offsets of the eight cells surrounding a center, in row-major order.
+const SURROUNDING: [Coord; 8] = [
+    Coord::new(-1, -1),
+    Coord::new(-1, 0),
+    Coord::new(-1, 1),
+    Coord::new(0, -1),
+    Coord::new(0, 1),
+    Coord::new(1, -1),
+    Coord::new(1, 0),
+    Coord::new(1, 1),
+];
+
+/// The offsets of the four cells directly next to a center, in row-major order.
+const NEIGHBORS: [Coord; 4] = [
+    Coord::new(-1, 0),
+    Coord::new(0, -1),
+    Coord::new(0, 1),
+    Coord::new(1, 0),
+];
+
+/// The coordinates around a center that lie inside a grid, in row-major order. The candidates
+/// are generated lazily from a fixed table of offsets, so no allocation is needed.
+#[derive(Debug, Clone)]
+pub struct Coords {
+    center: Coord,
+    max: Coord,
+    offsets: &'static [Coord],
+}
+
+impl Coords {
+    /// The up to eight coordinates surrounding |center|, including diagonals, in a grid whose
+    /// bottom right corner is |max|.
+    pub fn new_surrounding(max: Coord, center: Coord) -> Self {
+        Coords {
+            center,
+            max,
+            offsets: &SURROUNDING,
+        }
+    }
+
+    /// The up to four coordinates directly above, below, left and right of |center|.
+    pub fn new_neighbors(max: Coord, center: Coord) -> Self {
+        Coords {
+            center,
+            max,
+            offsets: &NEIGHBORS,
+        }
+    }
+
+    fn with_offsets(max: Coord, center: Coord, offsets: &'static [Coord]) -> Self {
+        Coords {
+            center,
+            max,
+            offsets,
+        }
+    }
+}
+
+/// Whether |coord| is inside a grid whose bottom right corner is |max|.
+fn within(coord: Coord, max: Coord) -> bool {
+    (0..=max.row).contains(&coord.row) && (0..=max.col).contains(&coord.col)
+}
+
+impl Iterator for Coords {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((offset, rest)) = self.offsets.split_first() {
+            self.offsets = rest;
+            let coord = self.center + *offset;
+            if within(coord, self.max) {
+                return Some(coord);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.offsets.len()))
+    }
+}
+
+/// Iterates over the coordinates around a cell of a grid.
+pub trait CoordIterator<T> {
+    fn surrounding_coords(&self, center: Coord) -> Coords;
+    fn neighbor_coords(&self, center: Coord) -> Coords;
+}
+
+/// A rule for which cells count as the neighbors of a cell.
+pub trait Neighborhood {
+    /// The neighbors of |center| in a grid whose bottom right corner is |max|.
+    fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord>;
+
+    /// A lower bound on the number of moves it takes to get from |from| to |to|, for use as a
+    /// search heuristic. Zero is always a valid answer.
+    fn min_steps(&self, _from: Coord, _to: Coord) -> u32 {
+        0
+    }
+}
+
+/// A neighborhood made of fixed offsets from the center.
+pub trait Stencil {
+    fn offsets(&self) -> &[Coord];
+}
+
+/// The four cells directly above, below, left and right.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VonNeumann;
+
+impl Stencil for VonNeumann {
+    fn offsets(&self) -> &[Coord] {
+        &NEIGHBORS
+    }
+}
+
+impl Neighborhood for VonNeumann {
+    fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord> {
+        Coords::new_neighbors(max, center)
+    }
+
+    fn min_steps(&self, from: Coord, to: Coord) -> u32 {
+        from.manhattan_distance(&to)
+    }
+}
+
+/// The eight cells around the center, including diagonals.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Moore;
+
+impl Stencil for Moore {
+    fn offsets(&self) -> &[Coord] {
+        &SURROUNDING
+    }
+}
+
+impl Neighborhood for Moore {
+    fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord> {
+        Coords::new_surrounding(max, center)
+    }
+
+    fn min_steps(&self, from: Coord, to: Coord) -> u32 {
+        from.chebyshev_distance(&to)
+    }
+}
+
+/// The offsets of the six cells around a hex in axial coordinates, in row-major order.
+const HEX: [Coord; 6] = [
+    Coord::new(-1, 0),
+    Coord::new(-1, 1),
+    Coord::new(0, -1),
+    Coord::new(0, 1),
+    Coord::new(1, -1),
+    Coord::new(1, 0),
+];
+
+/// The six cells around a hex, in axial coordinates: each row is shifted half a cell to the
+/// right of the row below it, so a hex touches two cells in the rows above and below.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hex;
+
+impl Stencil for Hex {
+    fn offsets(&self) -> &[Coord] {
+        &HEX
+    }
+}
+
+impl Neighborhood for Hex {
+    fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord> {
+        Coords::with_offsets(max, center, &HEX)
+    }
+
+    fn min_steps(&self, from: Coord, to: Coord) -> u32 {
+        let offset = to - from;
+        (offset.row.unsigned_abs()
+            + offset.col.unsigned_abs()
+            + (offset.row + offset.col).unsigned_abs())
+            / 2
+    }
+}
+
+/// How far apart two cells are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Orthogonal steps only.
+    Manhattan,
+    /// Diagonal steps allowed.
+    Chebyshev,
+}
+
+impl Metric {
+    pub fn distance(self, from: Coord, to: Coord) -> u32 {
+        match self {
+            Metric::Manhattan => from.manhattan_distance(&to),
+            Metric::Chebyshev => from.chebyshev_distance(&to),
+        }
+    }
+}
+
+/// Every cell within |radius| of the center under |metric|. A radius of 1 is |VonNeumann| for
+/// |Metric::Manhattan| and |Moore| for |Metric::Chebyshev|.
+#[derive(Debug, Clone)]
+pub struct Radius {
+    radius: u32,
+    metric: Metric,
+    offsets: Vec<Coord>,
+}
+
+impl Radius {
+    pub fn new(radius: u32, metric: Metric) -> Self {
+        let extent = radius as i32;
+        let offsets = (-extent..=extent)
+            .flat_map(|row| (-extent..=extent).map(move |col| Coord::new(row, col)))
+            .filter(|offset| {
+                *offset != Coord::ORIGIN && metric.distance(Coord::ORIGIN, *offset) <= radius
+            })
+            .collect();
+        Radius {
+            radius,
+            metric,
+            offsets,
+        }
+    }
+}
+
+impl Stencil for Radius {
+    fn offsets(&self) -> &[Coord] {
+        &self.offsets
+    }
+}
+
+impl Neighborhood for Radius {
+    fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord> {
+        self.offsets
+            .iter()
+            .map(move |offset| center + *offset)
+            .filter(move |coord| within(*coord, max))
+    }
+
+    fn min_steps(&self, from: Coord, to: Coord) -> u32 {
+        match self.radius {
+            0 => 0,
+            radius => self.metric.distance(from, to).div_ceil(radius),
+        }
+    }
+}
+
+/// Wraps a stencil around the edges of the grid, so that the first row neighbors the last and
+/// the first column neighbors the last. On grids smaller than the stencil, a cell can be
+/// reached through more than one offset and will be repeated.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Toroidal<S>(pub S);
+
+impl<S: Stencil> Neighborhood for Toroidal<S> {
+    fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord> {
+        let size = max + Coord::new(1, 1);
+        let offsets = if size.row > 0 && size.col > 0 {
+            self.0.offsets()
+        } else {
+            &[]
+        };
+        offsets.iter().map(move |offset| {
+            let coord = center + *offset;
+            Coord::new(
+                coord.row.rem_euclid(size.row),
+                coord.col.rem_euclid(size.col),
+            )
+        })
+    }
+}
+
+/// Looks from the center along each direction of a stencil, and takes the first cell that
+/// |stop| accepts. Directions that leave the grid first have no neighbor.
+#[derive(Debug, Clone)]
+pub struct Ray<S, F> {
+    directions: S,
+    stop: F,
+}
+
+impl<S: Stencil, F: Fn(Coord) -> bool> Ray<S, F> {
+    pub fn new(directions: S, stop: F) -> Self {
+        Ray { directions, stop }
+    }
+}
+
+impl<S: Stencil, F: Fn(Coord) -> bool> Neighborhood for Ray<S, F> {
+    fn neighbors(&self, max: Coord, center: Coord) -> impl Iterator<Item = Coord> {
+        self.directions
+            .offsets()
+            .iter()
+            .filter_map(move |direction| {
+                let mut coord = center + *direction;
+                while within(coord, max) {
+                    if (self.stop)(coord) {
+                        return Some(coord);
+                    }
+                    coord += *direction;
+                }
+                None
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coord(row: i32, col: i32) -> Coord {
+        Coord { row, col }
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(coord(0, 0).distance_to(&coord(3, 4)), 5.0);
+        assert_eq!(coord(3, 4).distance_to(&coord(0, 0)), 5.0);
+        assert_eq!(coord(2, 2).distance_to(&coord(2, 2)), 0.0);
+
+        assert_eq!(coord(-1, 2).manhattan_distance(&coord(3, -4)), 10);
+        assert_eq!(coord(-1, 2).chebyshev_distance(&coord(3, -4)), 6);
+        assert_eq!(coord(5, 5).chebyshev_distance(&coord(5, 5)), 0);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let mut position = coord(2, -3) + coord(-5, 1);
+        assert_eq!(position, coord(-3, -2));
+        position -= coord(1, 1);
+        assert_eq!(position, coord(-4, -3));
+        position += coord(4, 0);
+        assert_eq!(position, coord(0, -3));
+        assert_eq!(coord(2, -3) - coord(2, -3), Coord::ORIGIN);
+        assert_eq!(coord(2, -3) * 3, coord(6, -9));
+        assert_eq!(-coord(2, -3), coord(-2, 3));
+        assert_eq!(coord(-7, 0).signum(), coord(-1, 0));
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Coord::from_xy(4, 7), coord(7, 4));
+        assert_eq!((Coord::from_xy(4, 7).x(), Coord::from_xy(4, 7).y()), (4, 7));
+        assert_eq!(Coord::from((1, 2)), coord(1, 2));
+        assert_eq!(<(i32, i32)>::from(coord(1, 2)), (1, 2));
+        assert_eq!(Coord::from_indices(3, 9), coord(3, 9));
+        assert_eq!(coord(3, 9).to_indices(), Some((3, 9)));
+        assert_eq!(coord(3, -1).to_indices(), None);
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            coord(0, 9).line_to(coord(0, 6)).collect::<Vec<_>>(),
+            [coord(0, 9), coord(0, 8), coord(0, 7), coord(0, 6)]
+        );
+        assert_eq!(
+            coord(1, 1).line_to(coord(-1, 3)).collect::<Vec<_>>(),
+            [coord(1, 1), coord(0, 2), coord(-1, 3)]
+        );
+        assert_eq!(coord(4, 4).line_to(coord(4, 4)).count(), 1);
+        assert!(!coord(0, 0).is_aligned_with(&coord(1, 2)));
+    }
+
+    #[test]
+    fn directions() {
+        let start = coord(5, 5);
+        assert_eq!(start.step(Direction::Up), coord(4, 5));
+        assert_eq!(start.step(Direction::Left), coord(5, 4));
+
+        for direction in Direction::ALL {
+            assert_eq!(start.step(direction).step(direction.opposite()), start);
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.offset().manhattan_distance(&Coord::ORIGIN), 1);
+        }
+        assert_eq!(Direction::Left.turn_right(), Direction::Up);
+    }
+
+    #[test]
+    fn surrounding() {
+        let max = coord(2, 2);
+        assert_eq!(
+            Coords::new_surrounding(max, coord(1, 1)).collect::<Vec<_>>(),
+            [
+                coord(0, 0),
+                coord(0, 1),
+                coord(0, 2),
+                coord(1, 0),
+                coord(1, 2),
+                coord(2, 0),
+                coord(2, 1),
+                coord(2, 2)
+            ]
+        );
+        assert_eq!(
+            Coords::new_surrounding(max, coord(0, 0)).collect::<Vec<_>>(),
+            [coord(0, 1), coord(1, 0), coord(1, 1)]
+        );
+        assert_eq!(
+            Coords::new_surrounding(max, coord(2, 1)).collect::<Vec<_>>(),
+            [
+                coord(1, 0),
+                coord(1, 1),
+                coord(1, 2),
+                coord(2, 0),
+                coord(2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn surrounding_keeps_top_left_and_drops_center() {
+        // the original range filter dropped the top left corner instead of the center
+        let center = coord(5, 7);
+        let surrounding: Vec<_> = Coords::new_surrounding(coord(9, 9), center).collect();
+        assert_eq!(surrounding.len(), 8);
+        assert!(surrounding.contains(&coord(4, 6)));
+        assert!(!surrounding.contains(&center));
+        assert!(surrounding
+            .iter()
+            .all(|c| (c.row - center.row).abs() <= 1 && (c.col - center.col).abs() <= 1));
+    }
+
+    #[test]
+    fn neighbors() {
+        let max = coord(2, 2);
+        assert_eq!(
+            Coords::new_neighbors(max, coord(1, 1)).collect::<Vec<_>>(),
+            [coord(0, 1), coord(1, 0), coord(1, 2), coord(2, 1)]
+        );
+        assert_eq!(
+            Coords::new_neighbors(max, coord(2, 2)).collect::<Vec<_>>(),
+            [coord(1, 2), coord(2, 1)]
+        );
+        assert_eq!(
+            Coords::new_neighbors(max, coord(0, 1)).collect::<Vec<_>>(),
+            [coord(0, 0), coord(0, 2), coord(1, 1)]
+        );
+        assert_eq!(
+            Coords::new_neighbors(Coord::ORIGIN, Coord::ORIGIN).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn center_off_grid() {
+        let max = coord(2, 2);
+        assert_eq!(
+            Coords::new_surrounding(max, coord(3, 1)).collect::<Vec<_>>(),
+            [coord(2, 0), coord(2, 1), coord(2, 2)]
+        );
+        assert_eq!(
+            Coords::new_neighbors(max, coord(-1, 0)).collect::<Vec<_>>(),
+            [coord(0, 0)]
+        );
+        assert_eq!(Coords::new_surrounding(max, coord(5, 5)).count(), 0);
+        assert_eq!(
+            Coords::new_surrounding(max, coord(1, 1)).size_hint(),
+            (0, Some(8))
+        );
+    }
+
+    fn around(neighborhood: &impl Neighborhood, max: Coord, center: Coord) -> Vec<Coord> {
+        neighborhood.neighbors(max, center).collect()
+    }
+
+    #[test]
+    fn von_neumann_and_moore() {
+        let max = coord(2, 2);
+        for center in [coord(0, 0), coord(1, 1), coord(2, 1)] {
+            assert_eq!(
+                around(&VonNeumann, max, center),
+                Coords::new_neighbors(max, center).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                around(&Moore, max, center),
+                Coords::new_surrounding(max, center).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                around(&Radius::new(1, Metric::Manhattan), max, center),
+                around(&VonNeumann, max, center)
+            );
+            assert_eq!(
+                around(&Radius::new(1, Metric::Chebyshev), max, center),
+                around(&Moore, max, center)
+            );
+        }
+        assert_eq!(VonNeumann.min_steps(coord(0, 0), coord(2, 3)), 5);
+        assert_eq!(Moore.min_steps(coord(0, 0), coord(2, 3)), 3);
+    }
+
+    #[test]
+    fn radius() {
+        let max = coord(9, 9);
+        assert_eq!(
+            around(&Radius::new(2, Metric::Manhattan), max, coord(5, 5)).len(),
+            12
+        );
+        assert_eq!(
+            around(&Radius::new(2, Metric::Chebyshev), max, coord(5, 5)).len(),
+            24
+        );
+        assert_eq!(
+            around(&Radius::new(2, Metric::Manhattan), max, coord(0, 0)),
+            [
+                coord(0, 1),
+                coord(0, 2),
+                coord(1, 0),
+                coord(1, 1),
+                coord(2, 0)
+            ]
+        );
+        assert_eq!(
+            around(&Radius::new(0, Metric::Manhattan), max, coord(5, 5)),
+            []
+        );
+
+        let radius = Radius::new(3, Metric::Manhattan);
+        assert_eq!(radius.min_steps(coord(0, 0), coord(4, 3)), 3);
+        assert_eq!(radius.min_steps(coord(0, 0), coord(0, 3)), 1);
+    }
+
+    #[test]
+    fn hex() {
+        let max = coord(4, 4);
+        assert_eq!(
+            around(&Hex, max, coord(2, 2)),
+            [
+                coord(1, 2),
+                coord(1, 3),
+                coord(2, 1),
+                coord(2, 3),
+                coord(3, 1),
+                coord(3, 2)
+            ]
+        );
+        assert_eq!(around(&Hex, max, coord(0, 0)), [coord(0, 1), coord(1, 0)]);
+
+        // each neighbor is one step away, and opposite corners of a hex ring are two apart
+        for neighbor in around(&Hex, max, coord(2, 2)) {
+            assert_eq!(Hex.min_steps(coord(2, 2), neighbor), 1);
+        }
+        assert_eq!(Hex.min_steps(coord(1, 3), coord(3, 1)), 2);
+        assert_eq!(Hex.min_steps(coord(1, 2), coord(3, 2)), 2);
+        assert_eq!(Hex.min_steps(coord(0, 0), coord(2, 2)), 4);
+    }
+
+    #[test]
+    fn toroidal() {
+        let max = coord(2, 3);
+        assert_eq!(
+            around(&Toroidal(VonNeumann), max, coord(0, 0)),
+            [coord(2, 0), coord(0, 3), coord(0, 1), coord(1, 0)]
+        );
+        assert_eq!(around(&Toroidal(Moore), max, coord(2, 3)).len(), 8);
+        assert!(around(&Toroidal(Moore), max, coord(1, 1))
+            .iter()
+            .all(|coord| within(*coord, max)));
+        assert_eq!(around(&Toroidal(Moore), coord(-1, -1), Coord::ORIGIN), []);
+    }
+
+    #[test]
+    fn ray() {
+        // seats are the only cells a ray stops at
+        let seats = [coord(0, 2), coord(2, 0), coord(4, 4), coord(3, 2)];
+        let ray = Ray::new(Moore, |coord| seats.contains(&coord));
+        assert_eq!(
+            around(&ray, coord(4, 4), coord(2, 2)),
+            [coord(0, 2), coord(2, 0), coord(3, 2), coord(4, 4)]
+        );
+        assert_eq!(around(&ray, coord(1, 1), coord(1, 1)), []);
+
+        let ray = Ray::new(VonNeumann, |coord| seats.contains(&coord));
+        assert_eq!(
+            around(&ray, coord(4, 4), coord(2, 2)),
+            [coord(0, 2), coord(2, 0), coord(3, 2)]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::parse::ParseError;
+
+/// Everything that can go wrong while solving a puzzle, from unparseable input to input that
+/// parses but has no answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AocError {
+    /// The input could not be parsed.
+    Parse(ParseError),
+    /// The input holds nothing to compute an answer from.
+    EmptyInput,
+    /// The input parsed, but breaks one of the puzzle's rules.
+    InvalidInput(String),
+    /// There is no input file for a day.
+    InputNotFound { year: u16, day: u8, path: String },
+    /// A file could not be read or written.
+    Io { path: String, reason: String },
+    /// A day's input could not be downloaded.
+    Fetch { year: u16, day: u8, reason: String },
+    /// An answers file is not in the expected format.
+    InvalidAnswers(String),
+    /// A failure particular to one |day|'s puzzle, such as a bingo game that nobody wins.
+    Day { day: u8, source: Box<dyn DayError> },
+}
+
+/// An error that only one day's puzzle can raise, defined by the crate that solves it.
+///
+/// Any error type that can be cloned and compared qualifies, which keeps |AocError| cloneable and
+/// comparable when it wraps one.
+pub trait DayError: Error + Send + Sync + 'static {
+    fn clone_box(&self) -> Box<dyn DayError>;
+    fn eq_dyn(&self, other: &dyn DayError) -> bool;
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Error + Clone + Eq + Send + Sync + 'static> DayError for T {
+    fn clone_box(&self) -> Box<dyn DayError> {
+        Box::new(self.clone())
+    }
+
+    fn eq_dyn(&self, other: &dyn DayError) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn DayError> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl PartialEq for dyn DayError {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_dyn(other)
+    }
+}
+
+impl Eq for dyn DayError {}
+
+impl AocError {
+    /// The day-specific error this wraps, if it is a |T|.
+    pub fn day_error<T: DayError>(&self) -> Option<&T> {
+        match self {
+            AocError::Day { source, .. } => source.as_any().downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for AocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AocError::Parse(err) => write!(f, "{}", err),
+            AocError::EmptyInput => write!(f, "input is empty"),
+            AocError::InvalidInput(reason) => write!(f, "invalid input: {}", reason),
+            AocError::InputNotFound { year, day, path } => write!(
+                f,
+                "input not found for {} day {} (expected at {})",
+                year, day, path
+            ),
+            AocError::Io { path, reason } => write!(f, "{}: {}", path, reason),
+            AocError::Fetch { year, day, reason } => write!(
+                f,
+                "failed to download input for {} day {}: {}",
+                year, day, reason
+            ),
+            AocError::InvalidAnswers(reason) => write!(f, "invalid answers file: {}", reason),
+            AocError::Day { source, .. } => write!(f, "{}", source),
+        }
+    }
+}
+
+impl Error for AocError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AocError::Parse(err) => Some(err),
+            AocError::Day { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for AocError {
+    fn from(err: ParseError) -> Self {
+        AocError::Parse(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::ParseErrorKind;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Unlucky(u32);
+
+    impl Display for Unlucky {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{} is unlucky", self.0)
+        }
+    }
+
+    impl Error for Unlucky {}
+
+    #[test]
+    fn day_error() {
+        let err = AocError::Day {
+            day: 13,
+            source: Box::new(Unlucky(13)),
+        };
+        assert_eq!(err.to_string(), "13 is unlucky");
+        assert_eq!(err.day_error::<Unlucky>(), Some(&Unlucky(13)));
+        assert_eq!(err.day_error::<ParseError>(), None);
+        assert!(err.source().is_some());
+
+        assert_eq!(err.clone(), err);
+        assert_ne!(
+            err,
+            AocError::Day {
+                day: 13,
+                source: Box::new(Unlucky(7)),
+            }
+        );
+        assert_ne!(
+            err,
+            AocError::Day {
+                day: 14,
+                source: Box::new(Unlucky(13)),
+            }
+        );
+    }
+
+    #[test]
+    fn from_parse_error() {
+        let err: AocError = ParseError::new(3, 7, ParseErrorKind::UnexpectedChar('x')).into();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 7: unexpected character 'x'"
+        );
+        assert!(err.source().is_some());
+    }
+}
@@ -0,0 +1,317 @@
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+use crate::coord::{Coord, CoordIterator, Coords, Neighborhood};
+use crate::error::AocError;
+
+/// A rectangular grid stored contiguously in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// A |width| by |height| grid with every cell set to |value|.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Fails if the rows are not all the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, AocError> {
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some(row) = rows.iter().position(|row| row.len() != width) {
+            return Err(AocError::InvalidInput(format!(
+                "row {} is {} cells wide, but row 0 is {}",
+                row,
+                rows[row].len(),
+                width
+            )));
+        }
+
+        let height = if width == 0 { 0 } else { rows.len() };
+        Ok(Grid {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        })
+    }
+
+    /// |cells| must hold a whole number of rows of |width| cells.
+    pub(crate) fn from_cells(width: usize, cells: Vec<T>) -> Self {
+        assert!(width > 0 && cells.len().is_multiple_of(width));
+        Grid {
+            height: cells.len() / width,
+            cells,
+            width,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The coordinate of the bottom right cell.
+    pub fn max_coord(&self) -> Coord {
+        Coord::from_indices(self.height, self.width) - Coord::new(1, 1)
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        self.index_of(coord).is_some()
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.index_of(coord).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        self.index_of(coord).map(|index| &mut self.cells[index])
+    }
+
+    fn index_of(&self, coord: Coord) -> Option<usize> {
+        match coord.to_indices()? {
+            (row, col) if row < self.height && col < self.width => Some(row * self.width + col),
+            _ => None,
+        }
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.height, "row {} is outside the grid", row);
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "column {} is outside the grid", col);
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+
+    /// Every coordinate in the grid, in row-major order.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width;
+        (0..self.height)
+            .flat_map(move |row| (0..width).map(move |col| Coord::from_indices(row, col)))
+    }
+
+    /// Every cell with its coordinate, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.coords().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// The neighbors of |center| in this grid under |neighborhood|.
+    pub fn neighbors<'a>(
+        &self,
+        neighborhood: &'a impl Neighborhood,
+        center: Coord,
+    ) -> impl Iterator<Item = Coord> + 'a {
+        neighborhood.neighbors(self.max_coord(), center)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> CoordIterator<T> for Grid<T> {
+    fn surrounding_coords(&self, center: Coord) -> Coords {
+        Coords::new_surrounding(self.max_coord(), center)
+    }
+
+    fn neighbor_coords(&self, center: Coord) -> Coords {
+        Coords::new_neighbors(self.max_coord(), center)
+    }
+}
+
+/// Panics if |coord| is outside the grid.
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &T {
+        match self.index_of(coord) {
+            Some(index) => &self.cells[index],
+            None => panic!(
+                "{:?} is outside the {}x{} grid",
+                coord, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut T {
+        match self.index_of(coord) {
+            Some(index) => &mut self.cells[index],
+            None => panic!(
+                "{:?} is outside the {}x{} grid",
+                coord, self.width, self.height
+            ),
+        }
+    }
+}
+
+/// Writes each row on its own line, with no separators between cells.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::{Toroidal, VonNeumann};
+    use crate::parse;
+
+    fn digits(input: &str) -> Grid<u32> {
+        parse::grid(input, |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn dimensions() {
+        let grid = digits("123\n456\n");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.max_coord(), Coord::new(1, 2));
+        assert!(!grid.is_empty());
+
+        let empty = Grid::<u8>::from_rows(Vec::new()).unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+        assert!(empty.is_empty());
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.coords().count(), 0);
+        assert_eq!(empty.neighbor_coords(Coord::ORIGIN).count(), 0);
+        assert_eq!(empty.to_string(), "");
+    }
+
+    #[test]
+    fn from_rows() {
+        let grid = Grid::from_rows(vec![vec![1, 2], vec![3, 4]]).unwrap();
+        assert_eq!(grid, digits("12\n34"));
+        assert_eq!(
+            Grid::new(2, 1, 'x'),
+            Grid::from_rows(vec![vec!['x'; 2]]).unwrap()
+        );
+
+        assert!(matches!(
+            Grid::from_rows(vec![vec![1, 2], vec![3]]),
+            Err(AocError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn access() {
+        let mut grid = digits("123\n456\n");
+        assert_eq!(grid[Coord::new(1, 0)], 4);
+        assert_eq!(grid.get(Coord::new(0, 2)), Some(&3));
+        assert_eq!(grid.get(Coord::new(0, 3)), None);
+        assert_eq!(grid.get(Coord::new(2, 0)), None);
+        assert_eq!(grid.get(Coord::new(-1, 0)), None);
+        assert!(grid.contains(Coord::new(1, 2)));
+        assert!(!grid.contains(Coord::new(1, -1)));
+
+        grid[Coord::new(0, 0)] = 7;
+        *grid.get_mut(Coord::new(1, 2)).unwrap() += 1;
+        assert_eq!(grid.to_string(), "723\n457\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the 3x2 grid")]
+    fn index_out_of_bounds() {
+        let grid = digits("123\n456\n");
+        let _ = grid[Coord::new(0, 3)];
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = digits("123\n456\n");
+        assert_eq!(grid.row(1), [4, 5, 6]);
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), [2, 5]);
+        assert_eq!(
+            grid.columns()
+                .map(|column| column.sum::<u32>())
+                .collect::<Vec<_>>(),
+            [5, 7, 9]
+        );
+    }
+
+    #[test]
+    fn iteration() {
+        let mut grid = digits("12\n34\n");
+        assert_eq!(
+            grid.iter().collect::<Vec<_>>(),
+            [
+                (Coord::new(0, 0), &1),
+                (Coord::new(0, 1), &2),
+                (Coord::new(1, 0), &3),
+                (Coord::new(1, 1), &4)
+            ]
+        );
+
+        grid.values_mut().for_each(|value| *value *= 2);
+        assert_eq!(grid.values().sum::<u32>(), 20);
+        assert_eq!(
+            grid.map(|value| value % 4 == 0).to_string(),
+            "falsetrue\nfalsetrue\n"
+        );
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = digits("1234\n5678\n9012\n");
+        assert_eq!(grid.surrounding_coords(Coord::new(2, 3)).count(), 3);
+        assert_eq!(grid.neighbor_coords(Coord::new(1, 2)).count(), 4);
+        assert_eq!(
+            grid.neighbor_coords(Coord::new(0, 3))
+                .map(|coord| grid[coord])
+                .collect::<Vec<_>>(),
+            [3, 8]
+        );
+        assert_eq!(
+            grid.neighbors(&Toroidal(VonNeumann), Coord::new(0, 3))
+                .map(|coord| grid[coord])
+                .collect::<Vec<_>>(),
+            [2, 3, 1, 8]
+        );
+    }
+}
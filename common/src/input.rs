@@ -0,0 +1,150 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::answers::ANSWERS_FILE;
+use crate::error::AocError;
+
+/// The environment variable that overrides where inputs are read from.
+pub const DATA_DIR_ENV: &str = "AOC_DATA_DIR";
+
+/// Where puzzle inputs and answers live. By default these are the `data/` directories inside
+/// each year's crate of a checkout, e.g. `2021/data/day15`. A custom directory, such as one per
+/// user, instead holds a subdirectory per year, e.g. `2021/day15` and `2021/answers.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataDir {
+    /// The checkout that holds the current directory or, failing that, the executable. Found
+    /// when a path is needed, so the binary doesn't depend on where it was built.
+    Default,
+    /// The checkout at the given path.
+    Workspace(PathBuf),
+    Custom(PathBuf),
+}
+
+impl DataDir {
+    /// Uses |DATA_DIR_ENV| if it is set, and the default directories otherwise.
+    pub fn from_env() -> Self {
+        Self::from_env_or(DataDir::Default)
+    }
+
+    /// For tests and benches, which can pass their `CARGO_MANIFEST_DIR`: uses |DATA_DIR_ENV|
+    /// if it is set, and otherwise the checkout that holds the crate at |manifest_dir|.
+    pub fn from_env_or_crate(manifest_dir: &str) -> Self {
+        let crate_dir = Path::new(manifest_dir);
+        Self::from_env_or(DataDir::Workspace(
+            crate_dir.parent().unwrap_or(crate_dir).to_owned(),
+        ))
+    }
+
+    fn from_env_or(default: DataDir) -> Self {
+        match env::var_os(DATA_DIR_ENV) {
+            Some(dir) if !dir.is_empty() => DataDir::Custom(dir.into()),
+            _ => default,
+        }
+    }
+
+    pub fn year_dir(&self, year: u16) -> PathBuf {
+        match self {
+            DataDir::Default => find_year_dir(year),
+            DataDir::Workspace(root) => root.join(year.to_string()).join("data"),
+            DataDir::Custom(root) => root.join(year.to_string()),
+        }
+    }
+
+    pub fn input_path(&self, year: u16, day: u8) -> PathBuf {
+        self.year_dir(year).join(format!("day{}", day))
+    }
+
+    pub fn answers_path(&self, year: u16) -> PathBuf {
+        self.year_dir(year).join(ANSWERS_FILE)
+    }
+
+    /// Reads the input for |year| and |day|, failing with |AocError::InputNotFound| if there
+    /// is none.
+    pub fn read_input(&self, year: u16, day: u8) -> Result<String, AocError> {
+        let path = self.input_path(year, day);
+        fs::read_to_string(&path).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => AocError::InputNotFound {
+                year,
+                day,
+                path: path.display().to_string(),
+            },
+            _ => AocError::Io {
+                path: path.display().to_string(),
+                reason: err.to_string(),
+            },
+        })
+    }
+}
+
+/// The nearest `<year>/data` directory above the current directory, or above the executable,
+/// which cargo builds into the checkout's `target/` directory. If there is none, the path it
+/// would have under the current directory.
+fn find_year_dir(year: u16) -> PathBuf {
+    let relative = Path::new(&year.to_string()).join("data");
+    let current_dir = env::current_dir().unwrap_or_default();
+    let exe = env::current_exe().ok();
+
+    current_dir
+        .ancestors()
+        .chain(exe.iter().flat_map(|exe| exe.ancestors()))
+        .map(|dir| dir.join(&relative))
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| current_dir.join(&relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_dir() {
+        let path = DataDir::Default.input_path(2021, 15);
+        assert!(path.ends_with("2021/data/day15"));
+        assert!(path.is_absolute());
+    }
+
+    #[test]
+    fn workspace_dir() {
+        let dir = DataDir::Workspace(PathBuf::from("/src/aoc"));
+        assert_eq!(
+            dir.input_path(2021, 15),
+            Path::new("/src/aoc/2021/data/day15")
+        );
+        assert_eq!(
+            dir.answers_path(2022),
+            Path::new("/src/aoc/2022/data/answers.toml")
+        );
+    }
+
+    #[test]
+    fn custom_dir() {
+        let dir = DataDir::Custom(PathBuf::from("/home/elf/inputs"));
+        assert_eq!(
+            dir.input_path(2021, 15),
+            Path::new("/home/elf/inputs/2021/day15")
+        );
+        assert_eq!(
+            dir.answers_path(2022),
+            Path::new("/home/elf/inputs/2022/answers.toml")
+        );
+    }
+
+    #[test]
+    fn input_not_found() {
+        let dir = DataDir::Custom(env::temp_dir().join("aoc-input-does-not-exist"));
+        let err = dir.read_input(2021, 15).unwrap_err();
+        assert!(matches!(
+            err,
+            AocError::InputNotFound {
+                year: 2021,
+                day: 15,
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("input not found for 2021 day 15"));
+    }
+}
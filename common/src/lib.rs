@@ -0,0 +1,10 @@
+pub mod answers;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod coord;
+pub mod error;
+pub mod grid;
+pub mod input;
+pub mod parse;
+pub mod search;
+pub mod solution;
@@ -0,0 +1,257 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::grid::Grid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The input ended before everything that was expected had been read.
+    UnexpectedEnd,
+    /// A character that isn't valid at this position.
+    UnexpectedChar(char),
+    /// A token that should have been a number but couldn't be parsed as one.
+    InvalidNumber(String),
+    /// The input didn't have the expected structure, e.g. a missing separator.
+    Expected(&'static str),
+}
+
+/// An error encountered while parsing puzzle input. Lines and columns are 1-based, and columns
+/// count characters rather than bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, kind: ParseErrorKind) -> Self {
+        ParseError { line, column, kind }
+    }
+
+    /// An error positioned just past the last line of |input|.
+    pub fn end_of(input: &str) -> Self {
+        ParseError::new(input.lines().count() + 1, 1, ParseErrorKind::UnexpectedEnd)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            ParseErrorKind::InvalidNumber(token) => write!(f, "invalid number {:?}", token),
+            ParseErrorKind::Expected(what) => write!(f, "expected {}", what),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// A single line of puzzle input, along with its 1-based line number.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// The 1-based column at which |token| starts. |token| must be a slice of this line's text;
+    /// anything else is reported as the first column.
+    pub fn column_of(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (token.as_ptr() as usize).wrapping_sub(start);
+        if offset <= self.text.len() && self.text.is_char_boundary(offset) {
+            self.text[..offset].chars().count() + 1
+        } else {
+            1
+        }
+    }
+
+    /// An error pointing at |token|, which should be a slice of this line's text.
+    pub fn error_at(&self, token: &str, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(self.number, self.column_of(token), kind)
+    }
+
+    /// An error pointing just past the end of this line, for when something is missing.
+    pub fn error_at_end(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(self.number, self.text.chars().count() + 1, kind)
+    }
+
+    /// Parses |token|, a slice of this line's text, as a number.
+    pub fn parse_number<T: FromStr>(&self, token: &str) -> Result<T, ParseError> {
+        token
+            .parse()
+            .map_err(|_| self.error_at(token, ParseErrorKind::InvalidNumber(token.to_owned())))
+    }
+
+    /// Parses |token|, a slice of this line's text, with a |FromStr| implementation that reports
+    /// positions relative to |token|. The error is rebased to be relative to the whole input.
+    pub fn parse<T: FromStr<Err = ParseError>>(&self, token: &str) -> Result<T, ParseError> {
+        token.parse().map_err(|err: ParseError| {
+            ParseError::new(
+                self.number + err.line - 1,
+                self.column_of(token) + err.column - 1,
+                err.kind,
+            )
+        })
+    }
+
+    /// Maps every character of the line through |f|, reporting the first character for which |f|
+    /// returns |None|.
+    pub fn map_chars<T>(&self, mut f: impl FnMut(char) -> Option<T>) -> Result<Vec<T>, ParseError> {
+        self.text
+            .chars()
+            .enumerate()
+            .map(|(index, c)| {
+                f(c).ok_or_else(|| {
+                    ParseError::new(self.number, index + 1, ParseErrorKind::UnexpectedChar(c))
+                })
+            })
+            .collect()
+    }
+
+    /// Splits the line once on |separator|, erroring at the end of the line if it is missing.
+    pub fn split_once(
+        &self,
+        separator: &'static str,
+        expected: &'static str,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        self.text
+            .split_once(separator)
+            .ok_or_else(|| self.error_at_end(ParseErrorKind::Expected(expected)))
+    }
+}
+
+/// Iterates over the lines of |input| with their line numbers.
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(|(index, text)| Line {
+        number: index + 1,
+        text,
+    })
+}
+
+/// Iterates over the lines of |input| with their line numbers, skipping blank lines.
+pub fn non_empty_lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    lines(input).filter(|line| !line.text.trim().is_empty())
+}
+
+/// Parses a rectangular grid of characters, mapping each one through |f|. Blank lines are skipped,
+/// and every row must be the same length as the first.
+pub fn grid<T>(input: &str, mut f: impl FnMut(char) -> Option<T>) -> Result<Grid<T>, ParseError> {
+    let mut cells = Vec::new();
+    let mut width = None;
+    for line in non_empty_lines(input) {
+        let row = line.map_chars(&mut f)?;
+        match width {
+            None => width = Some(row.len()),
+            Some(width) if row.len() != width => {
+                return Err(
+                    line.error_at_end(ParseErrorKind::Expected("every row to be the same length"))
+                );
+            }
+            Some(_) => {}
+        }
+        cells.extend(row);
+    }
+
+    match width {
+        Some(width) => Ok(Grid::from_cells(width, cells)),
+        None => Err(ParseError::end_of(input)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_numbers_are_one_based() {
+        let numbers: Vec<usize> = lines("a\nb\n\nc").map(|line| line.number).collect();
+        assert_eq!(numbers, vec![1, 2, 3, 4]);
+
+        let numbers: Vec<usize> = non_empty_lines("a\nb\n\nc\n")
+            .map(|line| line.number)
+            .collect();
+        assert_eq!(numbers, vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn parse_number_reports_token_column() {
+        let line = lines("\n12 x4 7").nth(1).unwrap();
+        let tokens: Vec<&str> = line.text.split(' ').collect();
+
+        assert_eq!(line.parse_number::<u32>(tokens[0]), Ok(12));
+        assert_eq!(
+            line.parse_number::<u32>(tokens[1]),
+            Err(ParseError::new(
+                2,
+                4,
+                ParseErrorKind::InvalidNumber("x4".to_owned())
+            ))
+        );
+    }
+
+    #[test]
+    fn map_chars_reports_bad_char() {
+        let line = lines("123a5").next().unwrap();
+        let err = line.map_chars(|c| c.to_digit(10)).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(1, 4, ParseErrorKind::UnexpectedChar('a'))
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 4: unexpected character 'a'"
+        );
+    }
+
+    struct Pair(u8, u8);
+
+    impl FromStr for Pair {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let line = lines(s).next().unwrap();
+            let (left, right) = line.split_once(",", "`,`")?;
+            Ok(Pair(line.parse_number(left)?, line.parse_number(right)?))
+        }
+    }
+
+    #[test]
+    fn parse_rebases_errors() {
+        let line = lines("a\npair: 3,x").nth(1).unwrap();
+        let token = &line.text[6..];
+        assert_eq!(
+            line.parse::<Pair>(token).err(),
+            Some(ParseError::new(
+                2,
+                9,
+                ParseErrorKind::InvalidNumber("x".to_owned())
+            ))
+        );
+
+        let pair: Pair = line.parse("3,4").unwrap();
+        assert_eq!((pair.0, pair.1), (3, 4));
+    }
+
+    #[test]
+    fn end_of_input() {
+        assert_eq!(ParseError::end_of("a\nb\n").line, 3);
+        assert_eq!(ParseError::end_of("").line, 1);
+    }
+
+    #[test]
+    fn grid_requires_rectangle() {
+        let digits = grid("12\n34\n", |c| c.to_digit(10)).unwrap();
+        assert_eq!(digits.rows().collect::<Vec<_>>(), [[1, 2], [3, 4]]);
+
+        let err = grid("12\n345\n", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+
+        let err = grid("\n", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnexpectedEnd);
+    }
+}
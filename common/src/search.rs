@@ -0,0 +1,1155 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// What searches need from a step cost: a zero to start from, a way to add steps together and
+/// a way to compare totals.
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+/// A directed graph, described by the nodes reachable in one step from any node.
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+    type Cost: Cost;
+
+    /// The nodes reachable from |node| in one step, each with the cost of that step.
+    fn successors(&self, node: &Self::Node) -> impl Iterator<Item = (Self::Node, Self::Cost)>;
+}
+
+/// A |Graph| that can also list the steps into a node, which incremental searches need to
+/// repair their costs after a step cost changes.
+pub trait ReversibleGraph: Graph {
+    /// The nodes |node| is reachable from in one step, each with the cost of that step.
+    fn predecessors(&self, node: &Self::Node) -> impl Iterator<Item = (Self::Node, Self::Cost)>;
+}
+
+/// An estimate of the cost from a node to the goal, for searches that keep their heuristic
+/// around. Any |Fn(&N) -> C| is one.
+pub trait Heuristic<N, C> {
+    fn estimate(&self, node: &N) -> C;
+}
+
+impl<N, C, F: Fn(&N) -> C> Heuristic<N, C> for F {
+    fn estimate(&self, node: &N) -> C {
+        self(node)
+    }
+}
+
+/// A |Graph| backed by a function from a node to its successors.
+pub struct FromFn<N, C, F> {
+    successors: F,
+    marker: std::marker::PhantomData<fn(&N) -> C>,
+}
+
+/// Wraps |successors| as a |Graph|, for graphs that don't need a type of their own.
+pub fn from_fn<N, C, F, I>(successors: F) -> FromFn<N, C, F>
+where
+    F: Fn(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    FromFn {
+        successors,
+        marker: std::marker::PhantomData,
+    }
+}
+
+impl<N, C, F, I> Graph for FromFn<N, C, F>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    F: Fn(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    type Node = N;
+    type Cost = C;
+
+    fn successors(&self, node: &N) -> impl Iterator<Item = (N, C)> {
+        (self.successors)(node).into_iter()
+    }
+}
+
+/// What a search knows about a node it reached: the cost of the best path found to it, and
+/// the node that path came from, which is |None| for the start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visit<N, C> {
+    pub cost: C,
+    pub predecessor: Option<N>,
+}
+
+/// Where a search keeps a |Visit| for each node it reaches. |FxHashMap| works for any graph;
+/// graphs whose nodes can be laid out densely can provide something more compact.
+pub trait NodeMap<N, V> {
+    fn get(&self, node: &N) -> Option<&V>;
+    fn get_mut(&mut self, node: &N) -> Option<&mut V>;
+    fn insert(&mut self, node: N, value: V);
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every node in the map, in no particular order.
+    fn nodes(&self) -> impl Iterator<Item = N> + '_;
+}
+
+impl<N: Clone + Eq + Hash, V> NodeMap<N, V> for FxHashMap<N, V> {
+    fn get(&self, node: &N) -> Option<&V> {
+        FxHashMap::get(self, node)
+    }
+
+    fn get_mut(&mut self, node: &N) -> Option<&mut V> {
+        FxHashMap::get_mut(self, node)
+    }
+
+    fn insert(&mut self, node: N, value: V) {
+        FxHashMap::insert(self, node, value);
+    }
+
+    fn len(&self) -> usize {
+        FxHashMap::len(self)
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = N> + '_ {
+        self.keys().cloned()
+    }
+}
+
+/// Everything a search learned: the |Visit| to each node it reached, and the goal it stopped
+/// at, if any.
+#[derive(Debug, Clone)]
+pub struct SearchResult<N, C, M = FxHashMap<N, Visit<N, C>>> {
+    visits: M,
+    goal: Option<N>,
+    marker: std::marker::PhantomData<fn() -> C>,
+}
+
+impl<N: Clone, C: Copy, M: NodeMap<N, Visit<N, C>>> SearchResult<N, C, M> {
+    fn new(start: N, zero: C, mut visits: M) -> Self {
+        let start_visit = Visit {
+            cost: zero,
+            predecessor: None,
+        };
+        visits.insert(start, start_visit);
+        SearchResult {
+            visits,
+            goal: None,
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// The first goal node found, or |None| if the search ran out of nodes first.
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    pub fn goal_cost(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.cost(goal))
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.goal.as_ref().and_then(|goal| self.path_to(goal))
+    }
+
+    /// The cost of the best path found to |node|. For Dijkstra and breadth-first searches, and
+    /// for A* with a consistent heuristic, this is the cheapest path for every node that was
+    /// expanded before the search stopped.
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.visits.get(node).map(|visit| visit.cost)
+    }
+
+    pub fn predecessor(&self, node: &N) -> Option<&N> {
+        self.visits.get(node)?.predecessor.as_ref()
+    }
+
+    /// The path from the start to |node| inclusive, following predecessors back from |node|.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        self.visits.get(node)?;
+
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self.predecessor(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every node the search reached, in no particular order.
+    pub fn reached(&self) -> impl Iterator<Item = N> + '_ {
+        self.visits.nodes()
+    }
+
+    pub fn reached_count(&self) -> usize {
+        self.visits.len()
+    }
+
+    pub fn visits(&self) -> &M {
+        &self.visits
+    }
+}
+
+/// Searches outwards from |start| one step at a time, until a node satisfies |is_goal| or every
+/// reachable node has been visited. Costs count steps, ignoring the graph's step costs.
+pub fn bfs<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: impl FnMut(&G::Node) -> bool,
+) -> SearchResult<G::Node, usize> {
+    let mut result = SearchResult::new(start.clone(), 0, FxHashMap::default());
+    let mut queue = VecDeque::from([(start, 0)]);
+
+    while let Some((node, steps)) = queue.pop_front() {
+        if is_goal(&node) {
+            result.goal = Some(node);
+            break;
+        }
+
+        for (next, _) in graph.successors(&node) {
+            if let Entry::Vacant(entry) = result.visits.entry(next.clone()) {
+                entry.insert(Visit {
+                    cost: steps + 1,
+                    predecessor: Some(node.clone()),
+                });
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+
+    result
+}
+
+/// Searches as deep as possible along each branch before backtracking, until a node satisfies
+/// |is_goal| or every reachable node has been visited. Costs are depths in the search tree, so
+/// they are not shortest distances.
+pub fn dfs<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: impl FnMut(&G::Node) -> bool,
+) -> SearchResult<G::Node, usize> {
+    let mut result = SearchResult::new(start.clone(), 0, FxHashMap::default());
+    let mut visited = FxHashSet::default();
+    let mut stack = vec![(start, None, 0)];
+
+    while let Some((node, parent, depth)) = stack.pop() {
+        if !visited.insert(node.clone()) {
+            continue;
+        }
+        let visit = Visit {
+            cost: depth,
+            predecessor: parent,
+        };
+        result.visits.insert(node.clone(), visit);
+        if is_goal(&node) {
+            result.goal = Some(node);
+            break;
+        }
+
+        for (next, _) in graph.successors(&node) {
+            if !visited.contains(&next) {
+                stack.push((next, Some(node.clone()), depth + 1));
+            }
+        }
+    }
+
+    result
+}
+
+/// An entry in the open set of a best-first search. Entries are ordered so that
+/// |BinaryHeap|, a max-heap, pops the lowest priority first.
+struct Queued<N, C> {
+    priority: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Queued<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for Queued<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Queued<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Queued<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// Finds the cheapest path from |start| to a node that satisfies |is_goal|, expanding nodes in
+/// order of their cost from the start.
+pub fn dijkstra<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl FnMut(&G::Node) -> bool,
+) -> SearchResult<G::Node, G::Cost> {
+    a_star(graph, start, is_goal, |_| G::Cost::default())
+}
+
+/// Finds the cheapest path from |start| to a node that satisfies |is_goal|, expanding nodes in
+/// order of their cost from the start plus |heuristic|. The result is only guaranteed to be
+/// the cheapest if |heuristic| never overestimates the remaining cost.
+pub fn a_star<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl FnMut(&G::Node) -> bool,
+    heuristic: impl FnMut(&G::Node) -> G::Cost,
+) -> SearchResult<G::Node, G::Cost> {
+    a_star_with(graph, start, is_goal, heuristic, FxHashMap::default())
+}
+
+/// |a_star|, keeping what it learns about each node in |visits|.
+pub fn a_star_with<G: Graph, M: NodeMap<G::Node, Visit<G::Node, G::Cost>>>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl FnMut(&G::Node) -> bool,
+    heuristic: impl FnMut(&G::Node) -> G::Cost,
+    visits: M,
+) -> SearchResult<G::Node, G::Cost, M> {
+    best_first(graph, start, is_goal, heuristic, BinaryHeap::new(), visits)
+}
+
+/// A* for integer costs, using Dial's bucket queue instead of a heap: open nodes are kept in
+/// one bucket per priority, so pushing and popping don't compare anything. This suits graphs
+/// with small step costs and a consistent heuristic, one that never drops by more than the
+/// cost of a step, since the queue then only needs as many buckets as the largest step.
+pub fn dial<G: Graph<Cost = u32>>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl FnMut(&G::Node) -> bool,
+    heuristic: impl FnMut(&G::Node) -> u32,
+) -> SearchResult<G::Node, u32> {
+    dial_with(graph, start, is_goal, heuristic, FxHashMap::default())
+}
+
+/// |dial|, keeping what it learns about each node in |visits|.
+pub fn dial_with<G: Graph<Cost = u32>, M: NodeMap<G::Node, Visit<G::Node, u32>>>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl FnMut(&G::Node) -> bool,
+    heuristic: impl FnMut(&G::Node) -> u32,
+    visits: M,
+) -> SearchResult<G::Node, u32, M> {
+    best_first(graph, start, is_goal, heuristic, BucketQueue::new(), visits)
+}
+
+/// The open set of a best-first search, popping the entry with the lowest priority first.
+trait OpenSet<T, C> {
+    fn push(&mut self, priority: C, item: T);
+    fn pop(&mut self) -> Option<T>;
+}
+
+impl<T, C: Ord> OpenSet<T, C> for BinaryHeap<Queued<T, C>> {
+    fn push(&mut self, priority: C, node: T) {
+        BinaryHeap::push(self, Queued { priority, node });
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        BinaryHeap::pop(self).map(|queued| queued.node)
+    }
+}
+
+/// A priority queue for small integer priorities: a ring of buckets, one for each priority
+/// from the lowest one queued. It is fastest when priorities never drop below the last one
+/// popped, since the ring then only needs to grow when priorities spread further apart.
+struct BucketQueue<T> {
+    buckets: Vec<Vec<T>>,
+    /// The lowest priority that may still have items queued.
+    current: usize,
+    len: usize,
+}
+
+impl<T> BucketQueue<T> {
+    fn new() -> Self {
+        BucketQueue {
+            buckets: Vec::new(),
+            current: 0,
+            len: 0,
+        }
+    }
+
+    /// Moves every queued item into a ring of at least |span| buckets starting at priority
+    /// |lowest|, which must not be above any queued item.
+    fn rebase(&mut self, lowest: usize, span: usize) {
+        let old_len = self.buckets.len();
+        let new_len = span.next_power_of_two().max(8);
+        let mut buckets: Vec<Vec<T>> = (0..new_len).map(|_| Vec::new()).collect();
+        for offset in 0..old_len {
+            let priority = self.current + offset;
+            buckets[priority % new_len] = std::mem::take(&mut self.buckets[priority % old_len]);
+        }
+        self.buckets = buckets;
+        self.current = lowest;
+    }
+}
+
+impl<T> OpenSet<T, u32> for BucketQueue<T> {
+    fn push(&mut self, priority: u32, item: T) {
+        let priority = priority as usize;
+        if self.len == 0 {
+            self.current = priority;
+        } else if priority < self.current {
+            self.rebase(priority, self.current + self.buckets.len() - priority);
+        }
+        if priority - self.current >= self.buckets.len() {
+            self.rebase(self.current, priority - self.current + 1);
+        }
+
+        let bucket = priority % self.buckets.len();
+        self.buckets[bucket].push(item);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let len = self.buckets.len();
+        while self.buckets[self.current % len].is_empty() {
+            self.current += 1;
+        }
+        self.len -= 1;
+        self.buckets[self.current % len].pop()
+    }
+}
+
+fn best_first<G: Graph, M: NodeMap<G::Node, Visit<G::Node, G::Cost>>>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: impl FnMut(&G::Node) -> bool,
+    mut heuristic: impl FnMut(&G::Node) -> G::Cost,
+    mut open_set: impl OpenSet<(G::Node, G::Cost), G::Cost>,
+    visits: M,
+) -> SearchResult<G::Node, G::Cost, M> {
+    let zero = G::Cost::default();
+    let mut result = SearchResult::new(start.clone(), zero, visits);
+    open_set.push(heuristic(&start), (start, zero));
+
+    while let Some((node, cost)) = open_set.pop() {
+        // a cheaper path to this node was queued after this entry
+        if result.cost(&node).is_some_and(|best| best < cost) {
+            continue;
+        }
+        if is_goal(&node) {
+            result.goal = Some(node);
+            break;
+        }
+
+        for (next, step) in graph.successors(&node) {
+            let next_cost = cost + step;
+            let improved = match result.visits.get_mut(&next) {
+                Some(visit) if next_cost < visit.cost => {
+                    visit.cost = next_cost;
+                    visit.predecessor = Some(node.clone());
+                    true
+                }
+                Some(_) => false,
+                None => {
+                    let visit = Visit {
+                        cost: next_cost,
+                        predecessor: Some(node.clone()),
+                    };
+                    result.visits.insert(next.clone(), visit);
+                    true
+                }
+            };
+            if improved {
+                open_set.push(next_cost + heuristic(&next), (next, next_cost));
+            }
+        }
+    }
+
+    result
+}
+
+/// A path through a graph, from its first node to its last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    pub cost: C,
+    pub nodes: Vec<N>,
+}
+
+/// One direction of a bidirectional search: what it has reached and what it has yet to
+/// expand.
+struct Frontier<N, C> {
+    visits: FxHashMap<N, Visit<N, C>>,
+    open_set: BinaryHeap<Queued<(N, C), C>>,
+}
+
+impl<N: Clone + Eq + Hash, C: Cost> Frontier<N, C> {
+    fn new(start: N) -> Self {
+        let zero = C::default();
+        let visit = Visit {
+            cost: zero,
+            predecessor: None,
+        };
+        Frontier {
+            visits: FxHashMap::from_iter([(start.clone(), visit)]),
+            open_set: BinaryHeap::from([Queued {
+                priority: zero,
+                node: (start, zero),
+            }]),
+        }
+    }
+
+    /// A lower bound on the cost of every node this side has yet to expand.
+    fn lowest(&self) -> Option<C> {
+        self.open_set.peek().map(|queued| queued.priority)
+    }
+
+    /// Pops the cheapest node that is still worth expanding.
+    fn pop(&mut self) -> Option<(N, C)> {
+        while let Some(Queued {
+            node: (node, cost), ..
+        }) = self.open_set.pop()
+        {
+            if self.visits[&node].cost == cost {
+                return Some((node, cost));
+            }
+        }
+        None
+    }
+
+    /// Records reaching |next| from |node| at |cost|, if that is cheaper than before.
+    fn relax(&mut self, node: &N, next: N, cost: C) {
+        let improved = match self.visits.get(&next) {
+            Some(visit) => cost < visit.cost,
+            None => true,
+        };
+        if improved {
+            let visit = Visit {
+                cost,
+                predecessor: Some(node.clone()),
+            };
+            self.visits.insert(next.clone(), visit);
+            self.open_set.push(Queued {
+                priority: cost,
+                node: (next, cost),
+            });
+        }
+    }
+
+    /// The nodes from |node| back to where this side started, inclusive.
+    fn trace(&self, node: &N) -> Vec<N> {
+        let mut nodes = vec![node.clone()];
+        while let Some(previous) = &self.visits[nodes.last().unwrap()].predecessor {
+            nodes.push(previous.clone());
+        }
+        nodes
+    }
+}
+
+/// Finds the cheapest path from |start| to |goal| with two Dijkstra searches, one forwards
+/// from |start| and one backwards from |goal|, that stop once they meet. Each side only
+/// explores about as far as half the path, which for long paths is far fewer nodes than one
+/// search covering all of it.
+pub fn bidirectional_dijkstra<G: ReversibleGraph>(
+    graph: &G,
+    start: G::Node,
+    goal: G::Node,
+) -> Option<Path<G::Node, G::Cost>> {
+    let mut forward = Frontier::<_, G::Cost>::new(start);
+    let mut backward = Frontier::new(goal.clone());
+    let mut best = forward
+        .visits
+        .get(&goal)
+        .map(|_| (G::Cost::default(), goal));
+    let mut steps = Vec::new();
+
+    while let (Some(lowest_forward), Some(lowest_backward)) = (forward.lowest(), backward.lowest())
+    {
+        // every path not found yet costs at least this much
+        if best
+            .as_ref()
+            .is_some_and(|(cost, _)| lowest_forward + lowest_backward >= *cost)
+        {
+            break;
+        }
+
+        let is_forward = lowest_forward <= lowest_backward;
+        let (side, other) = if is_forward {
+            (&mut forward, &backward)
+        } else {
+            (&mut backward, &forward)
+        };
+        let Some((node, cost)) = side.pop() else {
+            continue;
+        };
+
+        steps.clear();
+        if is_forward {
+            steps.extend(graph.successors(&node));
+        } else {
+            steps.extend(graph.predecessors(&node));
+        }
+        for (next, step) in steps.drain(..) {
+            side.relax(&node, next.clone(), cost + step);
+            if let Some(rest) = other.visits.get(&next) {
+                let total = side.visits[&next].cost + rest.cost;
+                if best.as_ref().is_none_or(|(cost, _)| total < *cost) {
+                    best = Some((total, next));
+                }
+            }
+        }
+    }
+
+    let (cost, meeting) = best?;
+    let mut nodes = forward.trace(&meeting);
+    nodes.reverse();
+    nodes.extend(backward.trace(&meeting).into_iter().skip(1));
+    Some(Path { cost, nodes })
+}
+
+/// A |Graph| with some nodes and steps taken out.
+struct Restricted<'a, G: Graph> {
+    graph: &'a G,
+    removed_nodes: FxHashSet<G::Node>,
+    removed_steps: FxHashSet<(G::Node, G::Node)>,
+}
+
+impl<G: Graph> Graph for Restricted<'_, G> {
+    type Node = G::Node;
+    type Cost = G::Cost;
+
+    fn successors(&self, node: &G::Node) -> impl Iterator<Item = (G::Node, G::Cost)> {
+        self.graph.successors(node).filter(move |(next, _)| {
+            !self.removed_nodes.contains(next)
+                && !self.removed_steps.contains(&(node.clone(), next.clone()))
+        })
+    }
+}
+
+/// The cost of the cheapest step from |from| to |to|, if there is one.
+fn step_cost<G: Graph>(graph: &G, from: &G::Node, to: &G::Node) -> Option<G::Cost> {
+    graph
+        .successors(from)
+        .filter(|(next, _)| next == to)
+        .map(|(_, cost)| cost)
+        .min()
+}
+
+/// Finds up to |k| of the cheapest paths from |start| to |goal| that don't visit any node
+/// twice, cheapest first, using Yen's algorithm: each path after the first is the cheapest
+/// detour from a path already found, searched for with |a_star| and |heuristic|, which must
+/// never overestimate the remaining cost.
+pub fn k_shortest_paths<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    goal: G::Node,
+    k: usize,
+    mut heuristic: impl FnMut(&G::Node) -> G::Cost,
+) -> Vec<Path<G::Node, G::Cost>> {
+    let mut found: Vec<Path<G::Node, G::Cost>> = Vec::new();
+    if k == 0 {
+        return found;
+    }
+    let first = a_star(graph, start, |node| *node == goal, &mut heuristic);
+    let (Some(cost), Some(nodes)) = (first.goal_cost(), first.goal_path()) else {
+        return found;
+    };
+    found.push(Path { cost, nodes });
+
+    let mut candidates = BinaryHeap::new();
+    let mut seen = FxHashSet::default();
+    while found.len() < k {
+        let previous = found.last().unwrap().nodes.clone();
+        let mut root_cost = G::Cost::default();
+        for spur_index in 0..previous.len() - 1 {
+            let root = &previous[..=spur_index];
+            let spur = &previous[spur_index];
+
+            // leave the paths found so far that share this root, and the root itself
+            let mut restricted = Restricted {
+                graph,
+                removed_nodes: root[..spur_index].iter().cloned().collect(),
+                removed_steps: FxHashSet::default(),
+            };
+            for path in &found {
+                if path.nodes.len() > spur_index + 1 && path.nodes[..=spur_index] == *root {
+                    let step = (spur.clone(), path.nodes[spur_index + 1].clone());
+                    restricted.removed_steps.insert(step);
+                }
+            }
+
+            let detour = a_star(
+                &restricted,
+                spur.clone(),
+                |node| *node == goal,
+                &mut heuristic,
+            );
+            if let (Some(cost), Some(rest)) = (detour.goal_cost(), detour.goal_path()) {
+                let mut nodes = root[..spur_index].to_vec();
+                nodes.extend(rest);
+                if seen.insert(nodes.clone()) {
+                    candidates.push(Queued {
+                        priority: root_cost + cost,
+                        node: nodes,
+                    });
+                }
+            }
+
+            root_cost = root_cost + step_cost(graph, spur, &previous[spur_index + 1]).unwrap();
+        }
+
+        let Some(Queued { priority, node }) = candidates.pop() else {
+            break;
+        };
+        found.push(Path {
+            cost: priority,
+            nodes: node,
+        });
+    }
+
+    found
+}
+
+/// A path cost that may be infinite, for nodes that can't be reached (yet).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Distance<C> {
+    Finite(C),
+    Infinite,
+}
+
+impl<C: Cost> Distance<C> {
+    fn plus(self, cost: C) -> Self {
+        match self {
+            Distance::Finite(distance) => Distance::Finite(distance + cost),
+            Distance::Infinite => Distance::Infinite,
+        }
+    }
+}
+
+/// Nodes are expanded in order of their best known cost plus the heuristic, then of their
+/// best known cost.
+type Key<C> = (Distance<C>, Distance<C>);
+
+/// Lifelong Planning A*: finds the cheapest path from |start| to |goal| like |a_star|, then
+/// keeps it up to date as step costs change, re-expanding only the nodes whose cost the
+/// change affects. The graph is passed to each call rather than kept, so that it can be
+/// changed in between; after changing the cost of steps into a node, call |cost_changed| with
+/// that node before the next |compute|. |heuristic| must be consistent.
+pub struct LpaStar<N, C, H> {
+    start: N,
+    goal: N,
+    heuristic: H,
+    /// The cost of the best path to each node as of its last expansion.
+    g: FxHashMap<N, C>,
+    /// The cost of the best path to each node through its predecessors' |g|.
+    rhs: FxHashMap<N, C>,
+    open_set: BinaryHeap<Queued<N, Key<C>>>,
+    expanded: usize,
+}
+
+impl<N: Clone + Eq + Hash, C: Cost, H: Heuristic<N, C>> LpaStar<N, C, H> {
+    pub fn new(start: N, goal: N, heuristic: H) -> Self {
+        let mut search = LpaStar {
+            start: start.clone(),
+            goal,
+            heuristic,
+            g: FxHashMap::default(),
+            rhs: FxHashMap::from_iter([(start.clone(), C::default())]),
+            open_set: BinaryHeap::new(),
+            expanded: 0,
+        };
+        search.queue(start);
+        search
+    }
+
+    /// The number of nodes expanded so far, over every call to |compute|.
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+
+    /// The cost of the cheapest path to the goal as of the last |compute|.
+    pub fn cost(&self) -> Option<C> {
+        self.g.get(&self.goal).copied()
+    }
+
+    /// Brings the cheapest path to the goal up to date, and returns its cost, or |None| if
+    /// the goal can't be reached.
+    pub fn compute<G>(&mut self, graph: &G) -> Option<C>
+    where
+        G: ReversibleGraph<Node = N, Cost = C>,
+    {
+        while let Some(top) = self.open_set.peek() {
+            let node = top.node.clone();
+            let queued_key = top.priority;
+            let key = self.key(&node);
+            if self.g(&node) == self.rhs(&node) || key != queued_key {
+                // the node was expanded or requeued since this entry was pushed
+                self.open_set.pop();
+                continue;
+            }
+            if key >= self.key(&self.goal) && self.g(&self.goal) == self.rhs(&self.goal) {
+                break;
+            }
+
+            self.open_set.pop();
+            self.expanded += 1;
+            if self.g(&node) > self.rhs(&node) {
+                self.g.insert(node.clone(), self.rhs[&node]);
+            } else {
+                self.g.remove(&node);
+                self.update(graph, node.clone());
+            }
+            for (next, _) in graph.successors(&node) {
+                self.update(graph, next);
+            }
+        }
+        self.cost()
+    }
+
+    /// Records that the cost of one or more steps into |node| changed.
+    pub fn cost_changed<G>(&mut self, graph: &G, node: N)
+    where
+        G: ReversibleGraph<Node = N, Cost = C>,
+    {
+        self.update(graph, node);
+    }
+
+    /// The cheapest path from the start to the goal inclusive, as of the last |compute|.
+    pub fn path<G>(&self, graph: &G) -> Option<Vec<N>>
+    where
+        G: ReversibleGraph<Node = N, Cost = C>,
+    {
+        self.cost()?;
+
+        let mut path = vec![self.goal.clone()];
+        while *path.last().unwrap() != self.start {
+            let (previous, _) = graph
+                .predecessors(path.last().unwrap())
+                .map(|(previous, step)| {
+                    let distance = self.g(&previous).plus(step);
+                    (previous, distance)
+                })
+                .min_by_key(|(_, distance)| *distance)
+                .filter(|(_, distance)| *distance != Distance::Infinite)?;
+            path.push(previous);
+            if path.len() > self.g.len() {
+                return None;
+            }
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    fn g(&self, node: &N) -> Distance<C> {
+        self.g
+            .get(node)
+            .map_or(Distance::Infinite, |g| Distance::Finite(*g))
+    }
+
+    fn rhs(&self, node: &N) -> Distance<C> {
+        self.rhs
+            .get(node)
+            .map_or(Distance::Infinite, |rhs| Distance::Finite(*rhs))
+    }
+
+    fn key(&self, node: &N) -> Key<C> {
+        let best = self.g(node).min(self.rhs(node));
+        (best.plus(self.heuristic.estimate(node)), best)
+    }
+
+    fn queue(&mut self, node: N) {
+        let priority = self.key(&node);
+        self.open_set.push(Queued { priority, node });
+    }
+
+    /// Recomputes |rhs| for |node| from its predecessors, and queues it if that leaves it
+    /// inconsistent.
+    fn update<G>(&mut self, graph: &G, node: N)
+    where
+        G: ReversibleGraph<Node = N, Cost = C>,
+    {
+        if node != self.start {
+            let rhs = graph
+                .predecessors(&node)
+                .map(|(previous, step)| self.g(&previous).plus(step))
+                .min()
+                .unwrap_or(Distance::Infinite);
+            match rhs {
+                Distance::Finite(rhs) => self.rhs.insert(node.clone(), rhs),
+                Distance::Infinite => self.rhs.remove(&node),
+            };
+        }
+        if self.g(&node) != self.rhs(&node) {
+            self.queue(node);
+        }
+    }
+}
+
+/// Counts the distinct paths from |start| to nodes that satisfy |is_goal|, where a path ends at
+/// the first goal it reaches. Returns |None| if a cycle can be reached, since there are then
+/// infinitely many paths.
+pub fn count_paths<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: impl FnMut(&G::Node) -> bool,
+) -> Option<u64> {
+    let mut counts = FxHashMap::default();
+    let mut in_progress = FxHashSet::default();
+    count_paths_from(graph, start, &mut is_goal, &mut counts, &mut in_progress)
+}
+
+fn count_paths_from<G: Graph>(
+    graph: &G,
+    node: G::Node,
+    is_goal: &mut impl FnMut(&G::Node) -> bool,
+    counts: &mut FxHashMap<G::Node, u64>,
+    in_progress: &mut FxHashSet<G::Node>,
+) -> Option<u64> {
+    if let Some(count) = counts.get(&node) {
+        return Some(*count);
+    }
+    if is_goal(&node) {
+        return Some(1);
+    }
+    if !in_progress.insert(node.clone()) {
+        return None;
+    }
+
+    let mut count = 0;
+    let successors: Vec<_> = graph.successors(&node).map(|(next, _)| next).collect();
+    for next in successors {
+        count += count_paths_from(graph, next, is_goal, counts, in_progress)?;
+    }
+
+    in_progress.remove(&node);
+    counts.insert(node, count);
+    Some(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small weighted graph:
+    ///
+    /// ```text
+    ///   a --1--> b --1--> d --5--> e
+    ///   |        ^        ^
+    ///   4        1        1
+    ///   v        |        |
+    ///   c -------+--------+
+    /// ```
+    fn weighted() -> impl Graph<Node = char, Cost = u32> {
+        from_fn(|node: &char| match node {
+            'a' => vec![('b', 1), ('c', 4)],
+            'b' => vec![('d', 1)],
+            'c' => vec![('b', 1), ('d', 1)],
+            'd' => vec![('e', 5)],
+            _ => vec![],
+        })
+    }
+
+    #[test]
+    fn breadth_first() {
+        let result = bfs(&weighted(), 'a', |node| *node == 'e');
+        assert_eq!(result.goal(), Some(&'e'));
+        assert_eq!(result.goal_cost(), Some(3));
+        assert_eq!(result.goal_path(), Some(vec!['a', 'b', 'd', 'e']));
+        assert_eq!(result.cost(&'c'), Some(1));
+
+        let result = bfs(&weighted(), 'c', |_| false);
+        assert_eq!(result.goal(), None);
+        let mut reached: Vec<_> = result.reached().collect();
+        reached.sort();
+        assert_eq!(reached, ['b', 'c', 'd', 'e']);
+        assert_eq!(result.path_to(&'a'), None);
+    }
+
+    #[test]
+    fn depth_first() {
+        // successors are pushed in order, so the last one is explored first
+        let result = dfs(&weighted(), 'a', |node| *node == 'e');
+        assert_eq!(result.goal_path(), Some(vec!['a', 'c', 'd', 'e']));
+        assert_eq!(result.reached_count(), 4);
+
+        let result = dfs(&weighted(), 'a', |_| false);
+        assert_eq!(result.reached_count(), 5);
+        assert_eq!(result.predecessor(&'b'), Some(&'c'));
+    }
+
+    #[test]
+    fn cheapest_paths() {
+        let result = dijkstra(&weighted(), 'a', |node| *node == 'e');
+        assert_eq!(result.goal_cost(), Some(7));
+        assert_eq!(result.goal_path(), Some(vec!['a', 'b', 'd', 'e']));
+
+        let result = dijkstra(&weighted(), 'a', |_| false);
+        assert_eq!(
+            ['a', 'b', 'c', 'd', 'e'].map(|node| result.cost(&node)),
+            [Some(0), Some(1), Some(4), Some(2), Some(7)]
+        );
+
+        let result = dijkstra(&weighted(), 'e', |node| *node == 'a');
+        assert_eq!(result.goal(), None);
+        assert_eq!(result.goal_path(), None);
+    }
+
+    #[test]
+    fn a_star_on_a_line() {
+        // walking right along a number line, with a heuristic that is exact
+        let line = from_fn(|n: &i32| [(n + 1, 1u32), (n - 1, 1)]);
+        let result = a_star(&line, 0, |n| *n == 10, |n| n.abs_diff(10));
+        assert_eq!(result.goal_cost(), Some(10));
+        assert_eq!(result.goal_path(), Some((0..=10).collect()));
+        // an exact heuristic never looks left of the start
+        assert!(result.reached().all(|n| n >= -1));
+    }
+
+    #[test]
+    fn dials_algorithm() {
+        let result = dial(&weighted(), 'a', |node| *node == 'e', |_| 0);
+        assert_eq!(result.goal_cost(), Some(7));
+        assert_eq!(result.goal_path(), Some(vec!['a', 'b', 'd', 'e']));
+
+        let line = from_fn(|n: &i32| [(n + 1, 1u32), (n - 1, 1)]);
+        let result = dial(&line, 0, |n| *n == 10, |n| n.abs_diff(10));
+        assert_eq!(result.goal_path(), Some((0..=10).collect()));
+
+        // steps far more expensive than the initial number of buckets
+        let jumps = from_fn(|n: &u32| [(n + 1, 100), (n + 2, 1000)]);
+        let result = dial(&jumps, 0, |n| *n == 30, |_| 0);
+        assert_eq!(result.goal_cost(), Some(3000));
+    }
+
+    #[test]
+    fn bucket_queue() {
+        let mut queue = BucketQueue::new();
+        for (priority, item) in [(5, 'a'), (3, 'b'), (40, 'c'), (5, 'd'), (3, 'e')] {
+            queue.push(priority, item);
+        }
+        let mut popped = vec![queue.pop().unwrap(), queue.pop().unwrap()];
+        // below everything popped so far
+        queue.push(0, 'f');
+        popped.extend(std::iter::from_fn(|| queue.pop()));
+        assert_eq!(popped, ['e', 'b', 'f', 'd', 'a', 'c']);
+        assert_eq!(queue.len, 0);
+    }
+
+    /// A graph of weighted edges that can be changed. |edges| is the same graph as |weighted|.
+    struct Edges(Vec<(char, char, u32)>);
+
+    impl Graph for Edges {
+        type Node = char;
+        type Cost = u32;
+
+        fn successors(&self, node: &char) -> impl Iterator<Item = (char, u32)> {
+            let node = *node;
+            self.0
+                .iter()
+                .filter(move |(from, _, _)| *from == node)
+                .map(|(_, to, cost)| (*to, *cost))
+        }
+    }
+
+    impl ReversibleGraph for Edges {
+        fn predecessors(&self, node: &char) -> impl Iterator<Item = (char, u32)> {
+            let node = *node;
+            self.0
+                .iter()
+                .filter(move |(_, to, _)| *to == node)
+                .map(|(from, _, cost)| (*from, *cost))
+        }
+    }
+
+    #[test]
+    fn lifelong_planning() {
+        let mut graph = edges();
+        let mut search = LpaStar::new('a', 'e', |_: &char| 0);
+        assert_eq!(search.compute(&graph), Some(7));
+        assert_eq!(search.path(&graph), Some(vec!['a', 'b', 'd', 'e']));
+
+        graph.0[2].2 = 10;
+        search.cost_changed(&graph, 'd');
+        assert_eq!(search.compute(&graph), Some(10));
+        assert_eq!(search.path(&graph), Some(vec!['a', 'c', 'd', 'e']));
+
+        graph.0[2].2 = 1;
+        search.cost_changed(&graph, 'd');
+        let expanded = search.expanded();
+        assert_eq!(search.compute(&graph), Some(7));
+        assert_eq!(search.path(&graph), Some(vec!['a', 'b', 'd', 'e']));
+        // only d and e need another look
+        assert_eq!(search.expanded() - expanded, 2);
+
+        graph.0.pop();
+        search.cost_changed(&graph, 'e');
+        assert_eq!(search.compute(&graph), None);
+        assert_eq!(search.path(&graph), None);
+    }
+
+    fn edges() -> Edges {
+        Edges(vec![
+            ('a', 'b', 1),
+            ('a', 'c', 4),
+            ('b', 'd', 1),
+            ('c', 'b', 1),
+            ('c', 'd', 1),
+            ('d', 'e', 5),
+        ])
+    }
+
+    #[test]
+    fn bidirectional() {
+        let graph = edges();
+        assert_eq!(
+            bidirectional_dijkstra(&graph, 'a', 'e'),
+            Some(Path {
+                cost: 7,
+                nodes: vec!['a', 'b', 'd', 'e']
+            })
+        );
+        assert_eq!(
+            bidirectional_dijkstra(&graph, 'c', 'd').map(|path| path.cost),
+            Some(1)
+        );
+        assert_eq!(
+            bidirectional_dijkstra(&graph, 'b', 'b'),
+            Some(Path {
+                cost: 0,
+                nodes: vec!['b']
+            })
+        );
+        assert_eq!(bidirectional_dijkstra(&graph, 'e', 'a'), None);
+    }
+
+    #[test]
+    fn k_shortest() {
+        let paths = k_shortest_paths(&weighted(), 'a', 'e', 5, |_| 0);
+        assert_eq!(
+            paths
+                .iter()
+                .map(|path| (path.cost, path.nodes.iter().collect::<String>()))
+                .collect::<Vec<_>>(),
+            [
+                (7, "abde".to_owned()),
+                (10, "acde".to_owned()),
+                (11, "acbde".to_owned())
+            ]
+        );
+        assert_eq!(k_shortest_paths(&weighted(), 'a', 'e', 1, |_| 0).len(), 1);
+        assert!(k_shortest_paths(&weighted(), 'a', 'e', 0, |_| 0).is_empty());
+        assert!(k_shortest_paths(&weighted(), 'e', 'a', 3, |_| 0).is_empty());
+    }
+
+    #[test]
+    fn counting_paths() {
+        assert_eq!(count_paths(&weighted(), 'a', |node| *node == 'e'), Some(3));
+        assert_eq!(count_paths(&weighted(), 'a', |node| *node == 'b'), Some(2));
+        assert_eq!(count_paths(&weighted(), 'e', |node| *node == 'a'), Some(0));
+
+        let cycle = from_fn(|n: &u8| [((n + 1) % 3, 1u32)]);
+        assert_eq!(count_paths(&cycle, 0, |_| false), None);
+    }
+}
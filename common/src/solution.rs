@@ -0,0 +1,131 @@
+use std::fmt::Display;
+
+use crate::error::AocError;
+
+/// A single day's puzzle. The input is parsed once, and both parts are then solved from the
+/// parsed representation.
+pub trait Solution {
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(input: &str) -> Result<Self, AocError>
+    where
+        Self: Sized;
+
+    fn part1(&self) -> Result<Self::Answer1, AocError>;
+
+    fn part2(&self) -> Result<Self::Answer2, AocError>;
+}
+
+/// Object-safe version of |Solution| with the answers already formatted, so that days with
+/// different answer types can be stored side by side.
+pub trait DynSolution {
+    fn part1(&self) -> Result<String, AocError>;
+    fn part2(&self) -> Result<String, AocError>;
+}
+
+impl<S: Solution> DynSolution for S {
+    fn part1(&self) -> Result<String, AocError> {
+        Solution::part1(self).map(|answer| answer.to_string())
+    }
+
+    fn part2(&self) -> Result<String, AocError> {
+        Solution::part2(self).map(|answer| answer.to_string())
+    }
+}
+
+fn parse_boxed<S: Solution + 'static>(input: &str) -> Result<Box<dyn DynSolution>, AocError> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+/// A registry entry for an implemented day.
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub year: u16,
+    pub day: u8,
+    parse: fn(&str) -> Result<Box<dyn DynSolution>, AocError>,
+}
+
+impl Day {
+    pub const fn new<S: Solution + 'static>(year: u16, day: u8) -> Self {
+        Day {
+            year,
+            day,
+            parse: parse_boxed::<S>,
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Box<dyn DynSolution>, AocError> {
+        (self.parse)(input)
+    }
+}
+
+/// Finds the registry entry for |day| in a year's list of days.
+pub fn find_day(days: &[Day], day: u8) -> Option<&Day> {
+    days.iter().find(|entry| entry.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{lines, ParseError, ParseErrorKind};
+
+    struct Doubler(u32);
+
+    impl Solution for Doubler {
+        type Answer1 = u32;
+        type Answer2 = String;
+
+        fn parse(input: &str) -> Result<Self, AocError> {
+            let line = lines(input)
+                .next()
+                .ok_or_else(|| ParseError::end_of(input))?;
+            Ok(Doubler(line.parse_number(line.text)?))
+        }
+
+        fn part1(&self) -> Result<u32, AocError> {
+            Ok(self.0 * 2)
+        }
+
+        fn part2(&self) -> Result<String, AocError> {
+            match self.0 {
+                0 => Err(AocError::EmptyInput),
+                value => Ok(format!("{}{}", value, value)),
+            }
+        }
+    }
+
+    const DAYS: &[Day] = &[Day::new::<Doubler>(2000, 3)];
+
+    #[test]
+    fn dyn_solution_formats_answers() {
+        let solution = find_day(DAYS, 3).unwrap().parse("21\n").unwrap();
+        assert_eq!(solution.part1().unwrap(), "42");
+        assert_eq!(solution.part2().unwrap(), "2121");
+    }
+
+    #[test]
+    fn dyn_solution_parse_error() {
+        let err = find_day(DAYS, 3).unwrap().parse("2x\n").err().unwrap();
+        assert_eq!(
+            err,
+            AocError::Parse(ParseError::new(
+                1,
+                1,
+                ParseErrorKind::InvalidNumber("2x".to_owned())
+            ))
+        );
+    }
+
+    #[test]
+    fn dyn_solution_part_error() {
+        let solution = find_day(DAYS, 3).unwrap().parse("0\n").unwrap();
+        assert_eq!(solution.part1().unwrap(), "0");
+        assert_eq!(solution.part2().unwrap_err(), AocError::EmptyInput);
+    }
+
+    #[test]
+    fn find_day_missing() {
+        assert!(find_day(DAYS, 4).is_none());
+    }
+}